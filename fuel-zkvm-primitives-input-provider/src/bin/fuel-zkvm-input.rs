@@ -0,0 +1,81 @@
+//! Generates the inputs of the block execution game from a local fuel-core database.
+//!
+//! The database must be created with the `rewind-full-range` state rewind policy,
+//! so the state at the height preceding every requested block is still available.
+
+use anyhow::{anyhow, Context};
+use clap::Parser;
+use fuel_core::{
+    combined_database::CombinedDatabase,
+    state::{
+        historical_rocksdb::StateRewindPolicy,
+        rocks_db::{ColumnsPolicy, DatabaseConfig},
+    },
+};
+use fuel_core_types::fuel_types::BlockHeight;
+use fuel_zkvm_primitives_input_provider::input_provider::InputProvider;
+use fuel_zkvm_primitives_prover::games::block_execution_game::encode_input;
+use std::{fs, path::PathBuf};
+
+#[derive(Debug, Parser)]
+#[command(about = "Generates the block execution game inputs from a fuel-core database")]
+struct Args {
+    /// Path to the RocksDB directory of the fuel-core node.
+    #[arg(long)]
+    db_path: PathBuf,
+    /// Height of the first block to generate the input for.
+    #[arg(long)]
+    height: u32,
+    /// Height of the last block to generate the input for, the same as `height` by default.
+    #[arg(long)]
+    to_height: Option<u32>,
+    /// Directory the inputs are written to, one `block_<height>.bin` file per block.
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let to_height = args.to_height.unwrap_or(args.height);
+
+    if to_height < args.height {
+        return Err(anyhow!(
+            "The last height {to_height} is below the first height {}",
+            args.height
+        ));
+    }
+
+    let database = CombinedDatabase::open(
+        &args.db_path,
+        StateRewindPolicy::RewindFullRange,
+        DatabaseConfig {
+            cache_capacity: None,
+            max_fds: 512,
+            columns_policy: ColumnsPolicy::Lazy,
+        },
+    )
+    .with_context(|| format!("Unable to open the database {}", args.db_path.display()))?;
+
+    // The relayer database stores the events of all DA heights,
+    // so only the on-chain database needs to be viewed at the previous height.
+    let provider = InputProvider::new(database.on_chain().clone(), database.relayer().clone());
+
+    fs::create_dir_all(&args.output_dir)?;
+
+    for height in args.height..=to_height {
+        let input = provider
+            .input_at(BlockHeight::from(height))
+            .with_context(|| format!("Unable to generate the input for the block {height}"))?;
+
+        let path = args.output_dir.join(format!("block_{height}.bin"));
+        fs::write(&path, encode_input(&input)?)
+            .with_context(|| format!("Unable to write the input to {}", path.display()))?;
+
+        println!(
+            "Wrote the input for the block {height} to {}",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
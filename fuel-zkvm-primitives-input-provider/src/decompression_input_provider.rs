@@ -0,0 +1,290 @@
+use crate::registry_recorder::RegistryRecorder;
+use anyhow::{anyhow, Context};
+use fuel_core_compression::decompress::DecompressDb;
+use fuel_zkvm_primitives_prover::games::decompression_game::{
+    compressed_blocks, encode_input, order_blobs,
+    registry::{decompress_blocks, RegistryWitness},
+    Blob, Input,
+};
+use std::{fs, path::PathBuf, time::Duration};
+
+const BLOB_SIZE: usize = 131072;
+
+/// Builds the inputs of the decompression game from the blobs stored in local files.
+///
+/// A file holds either the raw blob bytes, a hex dump of them with an optional `0x` prefix,
+/// or the JSON returned by the `blob_sidecars` endpoint of the Ethereum beacon API.
+/// Raw and hex files may hold several blobs back to back, the last one is padded with zeroes.
+///
+/// All blobs must belong to a single bundle, they may come in any order and from any file.
+#[derive(Debug, Clone)]
+pub struct DecompressionInputProvider {
+    paths: Vec<PathBuf>,
+}
+
+impl DecompressionInputProvider {
+    pub fn new(paths: impl IntoIterator<Item = impl Into<PathBuf>>) -> Self {
+        Self {
+            paths: paths.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Returns the input with the blobs ordered by their position in the bundle.
+    pub fn input(&self) -> anyhow::Result<Input> {
+        let mut raw_da_blobs = Vec::new();
+
+        for path in &self.paths {
+            let bytes = fs::read(path)
+                .with_context(|| format!("Unable to read the blob file {}", path.display()))?;
+            let blobs = parse_blobs(&bytes)
+                .with_context(|| format!("Unable to parse the blob file {}", path.display()))?;
+            raw_da_blobs.extend(blobs);
+        }
+
+        let raw_da_blobs = order_blobs(raw_da_blobs)?;
+
+        Ok(Input {
+            raw_da_blobs,
+            registry: None,
+        })
+    }
+
+    /// Returns the [`input`](Self::input) along with the registry witness,
+    /// recorded by [`record_registry`] against the `db`.
+    pub fn input_with_registry<D: DecompressDb>(
+        &self,
+        db: D,
+        temporal_registry_retention: Duration,
+    ) -> anyhow::Result<Input> {
+        let mut input = self.input()?;
+
+        input.registry = Some(record_registry(
+            &input.raw_da_blobs,
+            db,
+            temporal_registry_retention,
+        )?);
+
+        Ok(input)
+    }
+
+    /// Returns the [`input`](Self::input) encoded for the decompression game.
+    pub fn encoded_input(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(encode_input(&self.input()?)?)
+    }
+}
+
+/// Decompresses the blocks of the bundle against the `db`, recording the registry witness
+/// of the decompression and the decompression execution games.
+///
+/// The `db` must hold the temporal registry as it was before the first block of the bundle.
+/// The values registered by the blocks are written to it.
+pub fn record_registry<D: DecompressDb>(
+    raw_da_blobs: &[Blob],
+    db: D,
+    temporal_registry_retention: Duration,
+) -> anyhow::Result<RegistryWitness> {
+    let blocks = compressed_blocks(raw_da_blobs.to_vec())?;
+
+    let mut recorder = RegistryRecorder::new(db, temporal_registry_retention);
+    decompress_blocks(temporal_registry_retention, &mut recorder, blocks)?;
+
+    Ok(recorder.into_witness())
+}
+
+/// The response of the `blob_sidecars` endpoint, or just the list of the sidecars.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum BlobSidecars {
+    Response { data: Vec<BlobSidecar> },
+    Sidecars(Vec<BlobSidecar>),
+}
+
+#[derive(serde::Deserialize)]
+struct BlobSidecar {
+    blob: String,
+}
+
+/// Parses the blobs from the contents of a blob file.
+///
+/// The contents that are neither JSON nor a hex dump are read as raw blobs.
+pub fn parse_blobs(bytes: &[u8]) -> anyhow::Result<Vec<Blob>> {
+    let text = std::str::from_utf8(bytes).map(str::trim);
+
+    match text {
+        Ok(text) if text.starts_with('{') || text.starts_with('[') => {
+            let sidecars = match serde_json::from_str(text)? {
+                BlobSidecars::Response { data } => data,
+                BlobSidecars::Sidecars(sidecars) => sidecars,
+            };
+
+            sidecars
+                .iter()
+                .map(|sidecar| -> anyhow::Result<Blob> {
+                    Ok(Blob::new(decode_hex(&sidecar.blob)?)?)
+                })
+                .collect()
+        }
+        Ok(text) if is_hex(text) => split_blobs(decode_hex(text)?),
+        _ => split_blobs(bytes.to_vec()),
+    }
+}
+
+fn is_hex(text: &str) -> bool {
+    let digits = text.strip_prefix("0x").unwrap_or(text);
+    !digits.is_empty()
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() || c.is_ascii_whitespace())
+}
+
+fn decode_hex(text: &str) -> anyhow::Result<Vec<u8>> {
+    let digits = text
+        .trim()
+        .strip_prefix("0x")
+        .unwrap_or(text.trim())
+        .split_ascii_whitespace()
+        .collect::<String>();
+
+    Ok(hex::decode(digits)?)
+}
+
+fn split_blobs(bytes: Vec<u8>) -> anyhow::Result<Vec<Blob>> {
+    if bytes.is_empty() {
+        return Err(anyhow!("The blob file is empty"));
+    }
+
+    bytes
+        .chunks(BLOB_SIZE)
+        .map(|chunk| -> anyhow::Result<Blob> { Ok(Blob::new(chunk.to_vec())?) })
+        .collect()
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input_provider::record_range_input,
+        test_helpers::{
+            blobs_of_bundle, two_chained_transfer_blocks, DaDb, NoEvents, TransferChain,
+        },
+    };
+    use fuel_core_types::fuel_tx::TxPointer;
+    use fuel_zkvm_primitives_prover::games::decompression_execution_game;
+
+    fn blob_prefix(blob: &Blob) -> [u8; 2] {
+        let bytes = Box::<[u8; BLOB_SIZE]>::from(blob.clone());
+        [bytes[0], bytes[1]]
+    }
+
+    #[test]
+    fn parse_blobs__reads_hex_dump() {
+        let blobs = parse_blobs(b"0x0102\n").unwrap();
+
+        assert_eq!(blobs.len(), 1);
+        assert_eq!(blob_prefix(&blobs[0]), [1, 2]);
+    }
+
+    #[test]
+    fn parse_blobs__reads_beacon_api_response() {
+        let response = br#"{"data": [
+            {"index": "0", "blob": "0x0102", "kzg_commitment": "0x"},
+            {"index": "1", "blob": "0x0304", "kzg_commitment": "0x"}
+        ]}"#;
+
+        let blobs = parse_blobs(response).unwrap();
+
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(blob_prefix(&blobs[0]), [1, 2]);
+        assert_eq!(blob_prefix(&blobs[1]), [3, 4]);
+    }
+
+    #[test]
+    fn parse_blobs__splits_raw_blobs() {
+        let mut bytes = vec![0; BLOB_SIZE];
+        bytes.extend([0, 5]);
+
+        let blobs = parse_blobs(&bytes).unwrap();
+
+        assert_eq!(blobs.len(), 2);
+        assert_eq!(blob_prefix(&blobs[1]), [0, 5]);
+    }
+
+    #[test]
+    fn parse_blobs_fails__if_file_is_empty() {
+        let result = parse_blobs(b"");
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn record_registry__records_witness_of_decompressed_blocks() {
+        let TransferChain {
+            secret,
+            genesis,
+            genesis_coin,
+            created_coin,
+            blocks,
+        } = two_chained_transfer_blocks();
+
+        // The history of the chain, with the registry as it was before the first block.
+        let history = || {
+            let da_db = DaDb::default();
+            da_db.add_coin(&secret, genesis_coin, 1000, TxPointer::default(), 0);
+            da_db.add_coin(&secret, created_coin, 1000, TxPointer::new(1.into(), 0), 0);
+            da_db
+        };
+        let retention = Duration::from_secs(3600);
+
+        let compressed_blocks = history().compress(retention, &blocks).await;
+        let raw_da_blobs = blobs_of_bundle(&compressed_blocks);
+
+        let registry = record_registry(&raw_da_blobs, history(), retention).unwrap();
+        let range = record_range_input(&genesis, NoEvents, blocks).unwrap();
+
+        assert!(registry.coins.contains_key(&genesis_coin));
+        assert!(registry.coins.contains_key(&created_coin));
+
+        let mut input = decompression_execution_game::Input {
+            raw_da_blobs,
+            registry,
+            blocks: range.blocks,
+            storage: range.storage,
+            ranges: range.ranges,
+            relayer: range.relayer,
+        };
+
+        let result = decompression_execution_game::prove(
+            &decompression_execution_game::encode_input(&input).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(result.first_block_height.to::<u32>(), 1);
+        assert_eq!(result.last_block_height.to::<u32>(), 2);
+        assert_eq!(
+            result.last_block_id.to_be_bytes::<32>(),
+            <[u8; 32]>::from(input.blocks[1].header().id())
+        );
+        assert_eq!(
+            result.registry_hash.to_be_bytes::<32>(),
+            <[u8; 32]>::from(input.registry.hash().unwrap())
+        );
+
+        // The first transfer decompresses with another amount of the spent coin.
+        if let Some(coin) = input.registry.coins.get_mut(&genesis_coin) {
+            coin.amount = 999;
+        }
+
+        let result = decompression_execution_game::prove(
+            &decompression_execution_game::encode_input(&input).unwrap(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(decompression_execution_game::Error::TransactionMismatch {
+                index: 0,
+                transaction_index: 0
+            })
+        ));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::{relayer_recorder::RelayerRecorder, storage_access_recorder::StorageAccessRecorder};
+use anyhow::anyhow;
+use fuel_core_executor::executor::{ExecutionInstance, ExecutionOptions};
+use fuel_core_relayer::storage::EventsHistory;
+use fuel_core_storage::{
+    column::Column,
+    kv_store::KeyValueInspect,
+    tables::{FuelBlocks, Transactions},
+    transactional::{AtomicView, Changes, ConflictPolicy, HistoricalView, StorageTransaction},
+    Error as StorageError, StorageAsRef, StorageInspect,
+};
+use fuel_core_types::{blockchain::block::Block, fuel_types::BlockHeight};
+use fuel_zkvm_primitives_prover::games::{
+    block_execution_game::Input,
+    block_range_execution_game::{self, check_prev_root, import_block},
+};
+
+/// Builds the inputs of the block execution game from the historical state of the chain.
+///
+/// The block is validated on top of the on-chain state at the previous height,
+/// while the storage and relayer accesses are recorded into the witnesses of the [`Input`].
+#[derive(Debug, Clone)]
+pub struct InputProvider<OnChain, Relayer> {
+    on_chain: OnChain,
+    relayer: Relayer,
+}
+
+impl<OnChain, Relayer> InputProvider<OnChain, Relayer> {
+    /// Creates the provider on top of the historical `on_chain` database
+    /// and the `relayer` database, that keeps the events of all DA heights.
+    pub fn new(on_chain: OnChain, relayer: Relayer) -> Self {
+        Self { on_chain, relayer }
+    }
+}
+
+impl<OnChain, Relayer> InputProvider<OnChain, Relayer>
+where
+    OnChain: AtomicView + HistoricalView<Height = BlockHeight>,
+    OnChain::LatestView: StorageInspect<FuelBlocks, Error = StorageError>
+        + StorageInspect<Transactions, Error = StorageError>,
+    OnChain::ViewAtHeight: KeyValueInspect<Column = Column>,
+    Relayer: AtomicView,
+    Relayer::LatestView: StorageInspect<EventsHistory, Error = StorageError>,
+{
+    /// Returns the input that proves the execution of the block at the `height`.
+    pub fn input_at(&self, height: BlockHeight) -> anyhow::Result<Input> {
+        let block = self.block_at(&height)?;
+        let previous_height = height
+            .pred()
+            .ok_or_else(|| anyhow!("The genesis block can't be proven"))?;
+
+        let storage = StorageAccessRecorder::new(self.on_chain.view_at(&previous_height)?);
+        let relayer = RelayerRecorder::new(self.relayer.latest_view()?);
+
+        let validator = ExecutionInstance::new(
+            relayer.clone(),
+            storage.clone(),
+            ExecutionOptions {
+                extra_tx_checks: true,
+                backtrace: false,
+            },
+        );
+        let _ = validator.validate_without_commit(&block)?;
+
+        let (storage, ranges) = storage.into_witness();
+        Ok(Input {
+            block,
+            storage,
+            ranges,
+            relayer: relayer.into_prover_relayer(),
+        })
+    }
+
+    /// Returns the input that proves the execution of the blocks
+    /// from the `first` to the `last` height, both inclusive.
+    pub fn range_input_at(
+        &self,
+        first: BlockHeight,
+        last: BlockHeight,
+    ) -> anyhow::Result<block_range_execution_game::Input> {
+        let previous_height = first
+            .pred()
+            .ok_or_else(|| anyhow!("The genesis block can't be proven"))?;
+
+        let blocks = (u32::from(first)..=u32::from(last))
+            .map(|height| self.block_at(&BlockHeight::from(height)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        record_range_input(
+            self.on_chain.view_at(&previous_height)?,
+            self.relayer.latest_view()?,
+            blocks,
+        )
+    }
+
+    fn block_at(&self, height: &BlockHeight) -> anyhow::Result<Block> {
+        let view = self.on_chain.latest_view()?;
+
+        let block = view
+            .storage_as_ref::<FuelBlocks>()
+            .get(height)?
+            .ok_or_else(|| anyhow!("Block at height {height} is not available"))?
+            .into_owned();
+
+        let transactions = block
+            .transactions()
+            .iter()
+            .map(|tx_id| {
+                view.storage_as_ref::<Transactions>()
+                    .get(tx_id)?
+                    .map(|tx| tx.into_owned())
+                    .ok_or_else(|| anyhow!("Transaction {tx_id} is not available"))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        Ok(block.uncompress(transactions))
+    }
+}
+
+/// Validates the `blocks` one after another on top of the `storage`, that holds the state
+/// preceding the first of them, recording the witnesses of the block range execution game.
+///
+/// Every block is checked against the root of the preceding blocks and imported
+/// before the next one is validated, the same way the game does,
+/// so the witness only holds the values the blocks didn't write themselves.
+pub fn record_range_input<S, R>(
+    storage: S,
+    relayer: R,
+    blocks: Vec<Block>,
+) -> anyhow::Result<block_range_execution_game::Input>
+where
+    S: KeyValueInspect<Column = Column>,
+    R: StorageInspect<EventsHistory, Error = StorageError>,
+{
+    let storage = StorageAccessRecorder::new(storage);
+    let relayer = RelayerRecorder::new(relayer);
+
+    {
+        let mut chain = StorageTransaction::transaction(
+            &storage,
+            ConflictPolicy::Overwrite,
+            Changes::default(),
+        );
+
+        for block in &blocks {
+            check_prev_root(&chain, block)?;
+
+            let validator = ExecutionInstance::new(
+                relayer.clone(),
+                &chain,
+                ExecutionOptions {
+                    extra_tx_checks: true,
+                    backtrace: false,
+                },
+            );
+            let changes = validator.validate_without_commit(block)?.into_changes();
+
+            import_block(&mut chain, block, changes)?;
+        }
+    }
+
+    let (storage, ranges) = storage.into_witness();
+    Ok(block_range_execution_game::Input {
+        blocks,
+        storage,
+        ranges,
+        relayer: relayer.into_prover_relayer(),
+    })
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{two_chained_transfer_blocks, NoEvents, TransferChain};
+    use fuel_core_types::fuel_tx::Bytes32;
+    use fuel_zkvm_primitives_prover::games::block_execution_game::{self, Relayer};
+
+    #[test]
+    fn record_range_input__records_witness_of_chained_blocks() {
+        let TransferChain {
+            genesis, blocks, ..
+        } = two_chained_transfer_blocks();
+
+        let mut input = record_range_input(&genesis, NoEvents, blocks).unwrap();
+
+        // The witness lacks the writes of the first block,
+        // so the second block can't be proven on its own.
+        let second_block_input = Input {
+            block: input.blocks[1].clone(),
+            storage: input.storage.clone(),
+            ranges: Vec::new(),
+            relayer: Relayer::new(),
+        };
+        let result = block_execution_game::prove(
+            &block_execution_game::encode_input(&second_block_input).unwrap(),
+        );
+        assert!(matches!(
+            result,
+            Err(block_execution_game::Error::MissingStorageWitness { .. })
+        ));
+
+        let result = block_range_execution_game::prove(
+            &block_range_execution_game::encode_input(&input).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(result.first_block_height.to::<u32>(), 1);
+        assert_eq!(result.last_block_height.to::<u32>(), 2);
+        assert_eq!(
+            result.last_block_id.to_be_bytes::<32>(),
+            <[u8; 32]>::from(input.blocks[1].header().id())
+        );
+
+        // The second block doesn't commit to the first one anymore.
+        input.blocks[1]
+            .header_mut()
+            .set_previous_root(Bytes32::zeroed());
+
+        let result = block_range_execution_game::prove(
+            &block_range_execution_game::encode_input(&input).unwrap(),
+        );
+
+        assert!(matches!(
+            result,
+            Err(block_range_execution_game::Error::PrevRootMismatch {
+                block_height: 2,
+                ..
+            })
+        ));
+    }
+}
@@ -4,6 +4,16 @@
 #![deny(warnings)]
 
 use tai64 as _;
+// Only used by the `fuel-zkvm-input` binary.
+#[cfg(feature = "cli")]
+use clap as _;
+#[cfg(feature = "cli")]
+use fuel_core as _;
 
+pub mod decompression_input_provider;
+pub mod input_provider;
+pub mod registry_recorder;
 pub mod relayer_recorder;
 pub mod storage_access_recorder;
+#[cfg(test)]
+mod test_helpers;
@@ -0,0 +1,223 @@
+use fuel_core_compression::ports::{CoinInfo, HistoryLookup, MessageInfo, TemporalRegistry};
+use fuel_core_types::{
+    fuel_compression::RegistryKey,
+    fuel_tx::{CompressedUtxoId, UtxoId},
+    fuel_types::Nonce,
+};
+use fuel_zkvm_primitives_prover::games::decompression_game::registry::{
+    Coin, Keyspace, Message, RegistryEntry, RegistryWitness,
+};
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
+use tai64::Tai64;
+
+/// Records the temporal registry entries and the history read while decompressing the blocks.
+///
+/// Only the first read of every key is recorded, and none of the keys the blocks registered
+/// themselves, so the witness holds the registry as it was before the first block.
+/// The registrations are written to the `db`.
+#[derive(Debug, Clone)]
+pub struct RegistryRecorder<D> {
+    db: D,
+    record: Arc<Mutex<RegistryWitness>>,
+    registered: Arc<Mutex<BTreeSet<(&'static str, RegistryKey)>>>,
+}
+
+impl<D> RegistryRecorder<D> {
+    pub fn new(db: D, temporal_registry_retention: Duration) -> Self {
+        let witness = RegistryWitness {
+            temporal_registry_retention,
+            ..Default::default()
+        };
+
+        Self {
+            db,
+            record: Arc::new(Mutex::new(witness)),
+            registered: Default::default(),
+        }
+    }
+
+    pub fn into_witness(self) -> RegistryWitness {
+        self.record
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    fn record(&self, f: impl FnOnce(&mut RegistryWitness)) {
+        f(&mut self.record.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    fn record_entry<T>(&self, key: &RegistryKey) -> anyhow::Result<RegistryEntry<T>>
+    where
+        D: TemporalRegistry<T>,
+        T: Keyspace,
+    {
+        let entry = RegistryEntry {
+            value: self.db.read_registry(key)?,
+            timestamp: self.db.read_timestamp(key)?.0,
+        };
+
+        let registered = self
+            .registered
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&(T::NAME, *key));
+        if registered {
+            return Ok(entry);
+        }
+
+        self.record(|witness| {
+            T::keyspace_mut(witness)
+                .entry(*key)
+                .or_insert_with(|| entry.clone());
+        });
+
+        Ok(entry)
+    }
+}
+
+impl<D, T> TemporalRegistry<T> for RegistryRecorder<D>
+where
+    D: TemporalRegistry<T>,
+    T: Keyspace,
+{
+    fn read_registry(&self, key: &RegistryKey) -> anyhow::Result<T> {
+        Ok(self.record_entry(key)?.value)
+    }
+
+    fn read_timestamp(&self, key: &RegistryKey) -> anyhow::Result<Tai64> {
+        Ok(Tai64(self.record_entry::<T>(key)?.timestamp))
+    }
+
+    fn write_registry(
+        &mut self,
+        key: &RegistryKey,
+        value: &T,
+        timestamp: Tai64,
+    ) -> anyhow::Result<()> {
+        self.registered
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((T::NAME, *key));
+
+        self.db.write_registry(key, value, timestamp)
+    }
+
+    fn registry_index_lookup(&self, value: &T) -> anyhow::Result<Option<RegistryKey>> {
+        self.db.registry_index_lookup(value)
+    }
+}
+
+impl<D> HistoryLookup for RegistryRecorder<D>
+where
+    D: HistoryLookup,
+{
+    fn utxo_id(&self, c: CompressedUtxoId) -> anyhow::Result<UtxoId> {
+        let utxo_id = self.db.utxo_id(c)?;
+
+        self.record(|witness| {
+            if !witness
+                .utxo_ids
+                .iter()
+                .any(|(compressed, _)| *compressed == c)
+            {
+                witness.utxo_ids.push((c, utxo_id));
+            }
+        });
+
+        Ok(utxo_id)
+    }
+
+    fn coin(&self, utxo_id: UtxoId) -> anyhow::Result<CoinInfo> {
+        let coin = self.db.coin(utxo_id)?;
+
+        self.record(|witness| {
+            witness.coins.entry(utxo_id).or_insert_with(|| Coin {
+                owner: coin.owner,
+                amount: coin.amount,
+                asset_id: coin.asset_id,
+            });
+        });
+
+        Ok(coin)
+    }
+
+    fn message(&self, nonce: Nonce) -> anyhow::Result<MessageInfo> {
+        let message = self.db.message(nonce)?;
+
+        self.record(|witness| {
+            witness.messages.entry(nonce).or_insert_with(|| Message {
+                sender: message.sender,
+                recipient: message.recipient,
+                amount: message.amount,
+                data: message.data.clone(),
+            });
+        });
+
+        Ok(message)
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fuel_core_types::fuel_tx::Address;
+
+    fn key(index: u32) -> RegistryKey {
+        RegistryKey::try_from(index).unwrap()
+    }
+
+    fn address_entry(byte: u8, timestamp: u64) -> RegistryEntry<Address> {
+        RegistryEntry {
+            value: Address::from([byte; 32]),
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn registry_recorder__records_only_first_reads() {
+        let mut db = RegistryWitness::default();
+        db.addresses.insert(key(1), address_entry(1, 1));
+        db.addresses.insert(key(2), address_entry(2, 1));
+        let mut recorder = RegistryRecorder::new(db, Duration::from_secs(10));
+
+        TemporalRegistry::<Address>::read_registry(&recorder, &key(1)).unwrap();
+        recorder
+            .write_registry(&key(1), &Address::from([3; 32]), Tai64(2))
+            .unwrap();
+        TemporalRegistry::<Address>::read_registry(&recorder, &key(1)).unwrap();
+
+        let witness = recorder.into_witness();
+        assert_eq!(witness.temporal_registry_retention, Duration::from_secs(10));
+        assert_eq!(witness.addresses.len(), 1);
+        assert_eq!(witness.addresses[&key(1)], address_entry(1, 1));
+    }
+
+    #[test]
+    fn registry_recorder__skips_keys_registered_by_blocks() {
+        let mut recorder = RegistryRecorder::new(RegistryWitness::default(), Duration::ZERO);
+
+        recorder
+            .write_registry(&key(1), &Address::from([3; 32]), Tai64(2))
+            .unwrap();
+        let address = TemporalRegistry::<Address>::read_registry(&recorder, &key(1)).unwrap();
+
+        assert_eq!(address, Address::from([3; 32]));
+        assert!(recorder.into_witness().addresses.is_empty());
+    }
+
+    #[test]
+    fn registry_recorder_fails__if_db_misses_key() {
+        let recorder = RegistryRecorder::new(RegistryWitness::default(), Duration::ZERO);
+
+        let result = TemporalRegistry::<Address>::read_timestamp(&recorder, &key(1));
+
+        assert!(result.is_err());
+        assert!(recorder.into_witness().addresses.is_empty());
+    }
+}
@@ -3,12 +3,14 @@ use fuel_core_relayer::storage::EventsHistory;
 use fuel_core_storage::{Error as StorageError, StorageAsRef, StorageInspect};
 use fuel_core_types::{blockchain::primitives::DaBlockHeight, services::relayer::Event};
 use fuel_zkvm_primitives_prover::games::block_execution_game::Relayer;
-use std::{cell::RefCell, sync::Arc};
+use std::sync::{Arc, Mutex, PoisonError};
 
+/// Records the relayer events requested during the execution,
+/// so a clone can be handed to the executor and the events read back afterwards.
 #[derive(Debug, Clone)]
 pub struct RelayerRecorder<S> {
     storage: S,
-    record: Arc<RefCell<Relayer>>,
+    record: Arc<Mutex<Relayer>>,
 }
 
 impl<S> RelayerRecorder<S> {
@@ -20,7 +22,10 @@ impl<S> RelayerRecorder<S> {
     }
 
     pub fn into_prover_relayer(self) -> Relayer {
-        self.record.borrow().clone()
+        self.record
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
 }
 
@@ -41,7 +46,8 @@ where
             .unwrap_or_default();
 
         self.record
-            .borrow_mut()
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
             .add_event(*da_height, events.clone());
 
         Ok(events)
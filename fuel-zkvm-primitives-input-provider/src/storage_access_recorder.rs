@@ -1,18 +1,24 @@
 use fuel_core_storage::{
-    kv_store::{KeyValueInspect, StorageColumn, Value, WriteOperation},
+    iter::{BoxedIter, IntoBoxedIter, IterDirection, IterableStore},
+    kv_store::{KVItem, KeyItem, KeyValueInspect, StorageColumn, Value, WriteOperation},
     transactional::Changes,
     Result as StorageResult,
 };
-use std::{cell::RefCell, sync::Arc};
+use fuel_zkvm_primitives_prover::games::block_execution_game::RangeRead;
+use std::sync::{Arc, Mutex, PoisonError};
 
 /// The in-memory storage for testing purposes.
-#[derive(Clone, Debug, PartialEq, Eq)]
+///
+/// Reads made through any clone, from any thread, land in the same witness.
+/// Iterations record their range along with every entry they yield.
+#[derive(Clone, Debug)]
 pub struct StorageAccessRecorder<S>
 where
     S: KeyValueInspect,
 {
     pub storage: S,
-    record: Arc<RefCell<Changes>>,
+    record: Arc<Mutex<Changes>>,
+    ranges: Arc<Mutex<Vec<RangeRead>>>,
 }
 
 impl<S> StorageAccessRecorder<S>
@@ -23,11 +29,41 @@ where
         Self {
             storage,
             record: Default::default(),
+            ranges: Default::default(),
         }
     }
 
     pub fn into_changes(self) -> Changes {
-        self.record.borrow().clone()
+        self.record
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns the storage witness along with the recorded range reads.
+    pub fn into_witness(self) -> (Changes, Vec<RangeRead>) {
+        let ranges = self
+            .ranges
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+
+        (self.into_changes(), ranges)
+    }
+}
+
+fn record_access(record: &Mutex<Changes>, column: u32, key: &[u8], value: Option<&Value>) {
+    let mut record = record.lock().unwrap_or_else(PoisonError::into_inner);
+    let tree = record.entry(column).or_default();
+
+    let key = key.to_vec().into();
+    match value {
+        Some(value) => {
+            tree.insert(key, WriteOperation::Insert(value.clone()));
+        }
+        None => {
+            tree.insert(key, WriteOperation::Remove);
+        }
     }
 }
 
@@ -39,19 +75,161 @@ where
 
     fn get(&self, key: &[u8], column: Self::Column) -> StorageResult<Option<Value>> {
         let value = self.storage.get(key, column)?;
+        record_access(&self.record, column.id(), key, value.as_ref());
+        Ok(value)
+    }
+}
 
-        let mut record = self.record.borrow_mut();
-        let tree = record.entry(column.id()).or_default();
-
-        let key = key.to_vec().into();
-        match &value {
-            Some(value) => {
-                tree.insert(key, WriteOperation::Insert(value.clone()));
-            }
-            None => {
-                tree.insert(key, WriteOperation::Remove);
+impl<S> IterableStore for StorageAccessRecorder<S>
+where
+    S: IterableStore,
+{
+    fn iter_store(
+        &self,
+        column: Self::Column,
+        prefix: Option<&[u8]>,
+        start: Option<&[u8]>,
+        direction: IterDirection,
+    ) -> BoxedIter<KVItem> {
+        let range = RangeRead::new(column.id(), prefix, start, direction);
+        {
+            let mut ranges = self.ranges.lock().unwrap_or_else(PoisonError::into_inner);
+            if !ranges.contains(&range) {
+                ranges.push(range);
             }
         }
-        Ok(value)
+
+        let record = self.record.clone();
+        let column_id = column.id();
+        self.storage
+            .iter_store(column, prefix, start, direction)
+            .inspect(move |item| {
+                if let Ok((key, value)) = item {
+                    record_access(&record, column_id, key, Some(value));
+                }
+            })
+            .into_boxed()
+    }
+
+    fn iter_store_keys(
+        &self,
+        column: Self::Column,
+        prefix: Option<&[u8]>,
+        start: Option<&[u8]>,
+        direction: IterDirection,
+    ) -> BoxedIter<KeyItem> {
+        // The guest serves the iteration from the witness, so the values are recorded too.
+        self.iter_store(column, prefix, start, direction)
+            .map(|item| item.map(|(key, _)| key))
+            .into_boxed()
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fuel_core_storage::column::Column;
+
+    /// Returns the key as the value for every key.
+    #[derive(Clone)]
+    struct EchoStorage;
+
+    impl KeyValueInspect for EchoStorage {
+        type Column = Column;
+
+        fn get(&self, key: &[u8], _: Self::Column) -> StorageResult<Option<Value>> {
+            Ok(Some(Value::from(key.to_vec())))
+        }
+    }
+
+    impl IterableStore for EchoStorage {
+        fn iter_store(
+            &self,
+            _: Self::Column,
+            _: Option<&[u8]>,
+            _: Option<&[u8]>,
+            _: IterDirection,
+        ) -> BoxedIter<KVItem> {
+            (0..3u8)
+                .map(|key| Ok((vec![key], Value::from(vec![key]))))
+                .into_boxed()
+        }
+
+        fn iter_store_keys(
+            &self,
+            column: Self::Column,
+            prefix: Option<&[u8]>,
+            start: Option<&[u8]>,
+            direction: IterDirection,
+        ) -> BoxedIter<KeyItem> {
+            self.iter_store(column, prefix, start, direction)
+                .map(|item| item.map(|(key, _)| key))
+                .into_boxed()
+        }
+    }
+
+    #[test]
+    fn get__records_accesses_from_multiple_threads() {
+        let recorder = StorageAccessRecorder::new(EchoStorage);
+
+        std::thread::scope(|scope| {
+            for thread in 0..4u8 {
+                let recorder = recorder.clone();
+                scope.spawn(move || {
+                    for key in 0..16u8 {
+                        recorder.get(&[thread, key], Column::Metadata).unwrap();
+                    }
+                });
+            }
+        });
+
+        let changes = recorder.into_changes();
+        assert_eq!(changes[&Column::Metadata.id()].len(), 64);
+    }
+
+    #[test]
+    fn iter_store_keys__records_range_and_yielded_entries() {
+        let recorder = StorageAccessRecorder::new(EchoStorage);
+
+        let keys = recorder
+            .iter_store_keys(
+                Column::Metadata,
+                Some(&[0][..]),
+                None,
+                IterDirection::Forward,
+            )
+            .take(2)
+            .collect::<StorageResult<Vec<_>>>()
+            .unwrap();
+        recorder
+            .iter_store_keys(
+                Column::Metadata,
+                Some(&[0][..]),
+                None,
+                IterDirection::Forward,
+            )
+            .for_each(drop);
+
+        let (changes, ranges) = recorder.into_witness();
+        assert_eq!(keys, vec![vec![0], vec![1]]);
+        assert_eq!(changes[&Column::Metadata.id()].len(), 3);
+        assert_eq!(
+            ranges,
+            vec![RangeRead::new(
+                Column::Metadata.id(),
+                Some(&[0][..]),
+                None,
+                IterDirection::Forward
+            )]
+        );
+    }
+
+    #[test]
+    fn recorders_are_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        assert_send_sync::<StorageAccessRecorder<EchoStorage>>();
+        assert_send_sync::<crate::relayer_recorder::RelayerRecorder<EchoStorage>>();
     }
 }
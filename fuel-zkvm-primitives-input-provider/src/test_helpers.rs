@@ -0,0 +1,371 @@
+//! Produces real blocks on top of an in-memory chain, so the recorded witnesses can be proven.
+
+use fuel_block_committer_encoding::{blob, bundle};
+use fuel_core_compression::{
+    compress::compress,
+    ports::{CoinInfo, EvictorDb, HistoryLookup, MessageInfo, TemporalRegistry, UtxoIdToPointer},
+    Config, VersionedCompressedBlock,
+};
+use fuel_core_executor::executor::{ExecutionInstance, ExecutionOptions, OnceTransactionsSource};
+use fuel_core_relayer::storage::EventsHistory;
+use fuel_core_storage::{
+    column::Column,
+    kv_store::KeyValueInspect,
+    rand::{prelude::StdRng, SeedableRng},
+    structured_storage::test::InMemoryStorage,
+    tables::{Coins, ConsensusParametersVersions, FuelBlocks},
+    transactional::{Changes, ConflictPolicy, StorageTransaction},
+    Error as StorageError, Mappable, StorageAsMut, StorageAsRef, StorageInspect,
+};
+use fuel_core_types::{
+    blockchain::{block::Block, header::PartialBlockHeader},
+    entities::coins::coin::Coin,
+    fuel_asm::{op, RegId},
+    fuel_compression::RegistryKey,
+    fuel_crypto::SecretKey,
+    fuel_tx::{
+        AssetId, Bytes32, CompressedUtxoId, ConsensusParameters, ContractId, Input, Output,
+        Transaction, TransactionBuilder, TxPointer, UniqueIdentifier, UtxoId,
+    },
+    fuel_types::{BlockHeight, ChainId, Nonce},
+    services::block_producer::Components,
+};
+use fuel_zkvm_primitives_prover::games::{
+    block_execution_game::Relayer,
+    block_range_execution_game::import_block,
+    decompression_game::{
+        registry::{Coin as RegistryCoin, Keyspace, RegistryWitness},
+        Blob,
+    },
+};
+use std::{borrow::Cow, cell::RefCell, collections::BTreeMap, rc::Rc, time::Duration};
+use tai64::Tai64;
+
+pub type GenesisStorage = StorageTransaction<InMemoryStorage<Column>>;
+
+/// Creates the genesis state with the default consensus parameters
+/// and a single base asset `coin` of the `amount` owned by the `secret`.
+pub fn genesis(secret: &SecretKey, coin: UtxoId, amount: u64) -> GenesisStorage {
+    let mut storage = StorageTransaction::transaction(
+        InMemoryStorage::default(),
+        ConflictPolicy::Overwrite,
+        Changes::default(),
+    );
+
+    storage
+        .storage_as_mut::<ConsensusParametersVersions>()
+        .insert(&0, &ConsensusParameters::default())
+        .unwrap();
+
+    storage
+        .storage_as_mut::<FuelBlocks>()
+        .insert(
+            &BlockHeight::from(0),
+            &Block::default().compress(&ChainId::default()),
+        )
+        .unwrap();
+
+    let coin = Coin {
+        utxo_id: coin,
+        owner: Input::owner(&secret.public_key()),
+        amount,
+        asset_id: AssetId::BASE,
+        tx_pointer: TxPointer::default(),
+    };
+    storage
+        .storage_as_mut::<Coins>()
+        .insert(&coin.utxo_id, &coin.compress())
+        .unwrap();
+
+    storage
+}
+
+/// Returns the transaction that sends the base asset `coin` of the `amount`
+/// back to the owner `secret`, creating a new coin at the output `0`.
+pub fn transfer(secret: &SecretKey, coin: UtxoId, amount: u64) -> Transaction {
+    let owner = Input::owner(&secret.public_key());
+
+    TransactionBuilder::script(vec![op::ret(RegId::ONE)].into_iter().collect(), vec![])
+        .script_gas_limit(100_000)
+        .add_unsigned_coin_input(*secret, coin, amount, AssetId::BASE, TxPointer::default())
+        .add_output(Output::coin(owner, amount, AssetId::BASE))
+        .finalize_as_transaction()
+}
+
+/// Produces the block at the `height` with the `transactions` on top of the `chain`
+/// and imports it, the same way the block range execution game does.
+/// Like the block producer of fuel-core, commits to the blocks before it with the `prev_root`.
+pub fn produce_block<S>(
+    chain: &mut StorageTransaction<S>,
+    height: u32,
+    transactions: Vec<Transaction>,
+) -> Block
+where
+    S: KeyValueInspect<Column = Column>,
+{
+    let mut header = PartialBlockHeader::default();
+    header.consensus.height = BlockHeight::from(height);
+    let previous_height = BlockHeight::from(height)
+        .pred()
+        .expect("The genesis block can't be produced");
+    header.consensus.prev_root = chain
+        .storage_as_ref::<FuelBlocks>()
+        .root(&previous_height)
+        .expect("Unable to read the root of the preceding blocks")
+        .into();
+
+    let producer = ExecutionInstance::new(
+        Relayer::new(),
+        &*chain,
+        ExecutionOptions {
+            extra_tx_checks: true,
+            backtrace: false,
+        },
+    );
+
+    let (result, changes) = producer
+        .produce_without_commit(
+            Components {
+                header_to_produce: header,
+                transactions_source: OnceTransactionsSource::new(transactions),
+                coinbase_recipient: ContractId::zeroed(),
+                gas_price: 0,
+            },
+            false,
+        )
+        .expect("Unable to produce the block")
+        .into();
+
+    assert!(
+        result.skipped_transactions.is_empty(),
+        "Transactions were skipped: {:?}",
+        result.skipped_transactions
+    );
+
+    import_block(chain, &result.block, changes).expect("Unable to import the block");
+
+    result.block
+}
+
+/// The genesis state along with the two blocks produced on top of it.
+pub struct TransferChain {
+    pub secret: SecretKey,
+    pub genesis: GenesisStorage,
+    /// The coin of the genesis state, spent by the first block.
+    pub genesis_coin: UtxoId,
+    /// The coin created by the first block and spent by the second one.
+    pub created_coin: UtxoId,
+    pub blocks: Vec<Block>,
+}
+
+/// Produces two chained blocks, each with a single [`transfer`] of the coin
+/// the previous one created, starting with the coin of the genesis state.
+pub fn two_chained_transfer_blocks() -> TransferChain {
+    let secret = SecretKey::random(&mut StdRng::seed_from_u64(2322u64));
+    let genesis_coin = UtxoId::new(Bytes32::from([1; 32]), 0);
+    let genesis = genesis(&secret, genesis_coin, 1000);
+
+    let first_transfer = transfer(&secret, genesis_coin, 1000);
+    let created_coin = UtxoId::new(first_transfer.id(&ChainId::default()), 0);
+    let second_transfer = transfer(&secret, created_coin, 1000);
+
+    let blocks = {
+        let mut chain = StorageTransaction::transaction(
+            &genesis,
+            ConflictPolicy::Overwrite,
+            Changes::default(),
+        );
+        vec![
+            produce_block(&mut chain, 1, vec![first_transfer]),
+            produce_block(&mut chain, 2, vec![second_transfer]),
+        ]
+    };
+
+    TransferChain {
+        secret,
+        genesis,
+        genesis_coin,
+        created_coin,
+        blocks,
+    }
+}
+
+/// The relayer database without any events.
+pub struct NoEvents;
+
+impl StorageInspect<EventsHistory> for NoEvents {
+    type Error = StorageError;
+
+    fn get(
+        &self,
+        _: &<EventsHistory as Mappable>::Key,
+    ) -> Result<Option<Cow<<EventsHistory as Mappable>::OwnedValue>>, Self::Error> {
+        Ok(None)
+    }
+
+    fn contains_key(&self, _: &<EventsHistory as Mappable>::Key) -> Result<bool, Self::Error> {
+        Ok(false)
+    }
+}
+
+/// The DA compression database: the temporal registry along with the coins
+/// the compressed inputs point at.
+///
+/// Clones share the same database, as the compression and the decompression take it by value.
+#[derive(Debug, Clone, Default)]
+pub struct DaDb(Rc<RefCell<DaState>>);
+
+#[derive(Debug, Default)]
+struct DaState {
+    registry: RegistryWitness,
+    latest_keys: BTreeMap<&'static str, RegistryKey>,
+    coins: Vec<(CompressedUtxoId, UtxoId, RegistryCoin)>,
+}
+
+impl DaDb {
+    /// Adds the base asset `coin` of the `amount` owned by the `secret`,
+    /// created at the `output_index` of the transaction at the `tx_pointer`.
+    pub fn add_coin(
+        &self,
+        secret: &SecretKey,
+        coin: UtxoId,
+        amount: u64,
+        tx_pointer: TxPointer,
+        output_index: u16,
+    ) {
+        let compressed = CompressedUtxoId {
+            tx_pointer,
+            output_index,
+        };
+        let info = RegistryCoin {
+            owner: Input::owner(&secret.public_key()),
+            amount,
+            asset_id: AssetId::BASE,
+        };
+
+        self.0.borrow_mut().coins.push((compressed, coin, info));
+    }
+
+    /// Compresses the `blocks` one after another, registering their values.
+    pub async fn compress(
+        &self,
+        temporal_registry_retention: Duration,
+        blocks: &[Block],
+    ) -> Vec<VersionedCompressedBlock> {
+        let mut compressed_blocks = Vec::new();
+
+        for block in blocks {
+            let config = Config {
+                temporal_registry_retention,
+            };
+            let compressed_block = compress(config, self.clone(), block)
+                .await
+                .expect("Unable to compress the block");
+            compressed_blocks.push(compressed_block);
+        }
+
+        compressed_blocks
+    }
+}
+
+impl<T> TemporalRegistry<T> for DaDb
+where
+    RegistryWitness: TemporalRegistry<T>,
+{
+    fn read_registry(&self, key: &RegistryKey) -> anyhow::Result<T> {
+        TemporalRegistry::<T>::read_registry(&self.0.borrow().registry, key)
+    }
+
+    fn read_timestamp(&self, key: &RegistryKey) -> anyhow::Result<Tai64> {
+        TemporalRegistry::<T>::read_timestamp(&self.0.borrow().registry, key)
+    }
+
+    fn write_registry(
+        &mut self,
+        key: &RegistryKey,
+        value: &T,
+        timestamp: Tai64,
+    ) -> anyhow::Result<()> {
+        self.0
+            .borrow_mut()
+            .registry
+            .write_registry(key, value, timestamp)
+    }
+
+    fn registry_index_lookup(&self, value: &T) -> anyhow::Result<Option<RegistryKey>> {
+        self.0.borrow().registry.registry_index_lookup(value)
+    }
+}
+
+impl<T: Keyspace> EvictorDb<T> for DaDb {
+    fn set_latest_assigned_key(&mut self, key: RegistryKey) -> anyhow::Result<()> {
+        self.0.borrow_mut().latest_keys.insert(T::NAME, key);
+        Ok(())
+    }
+
+    fn get_latest_assigned_key(&self) -> anyhow::Result<Option<RegistryKey>> {
+        Ok(self.0.borrow().latest_keys.get(T::NAME).copied())
+    }
+}
+
+impl UtxoIdToPointer for DaDb {
+    fn lookup(&self, utxo_id: UtxoId) -> anyhow::Result<CompressedUtxoId> {
+        self.0
+            .borrow()
+            .coins
+            .iter()
+            .find(|(_, coin, _)| *coin == utxo_id)
+            .map(|(compressed, _, _)| *compressed)
+            .ok_or_else(|| anyhow::anyhow!("Unknown coin {utxo_id:?}"))
+    }
+}
+
+impl HistoryLookup for DaDb {
+    fn utxo_id(&self, c: CompressedUtxoId) -> anyhow::Result<UtxoId> {
+        self.0
+            .borrow()
+            .coins
+            .iter()
+            .find(|(compressed, _, _)| *compressed == c)
+            .map(|(_, coin, _)| *coin)
+            .ok_or_else(|| anyhow::anyhow!("Unknown coin pointer {c:?}"))
+    }
+
+    fn coin(&self, utxo_id: UtxoId) -> anyhow::Result<CoinInfo> {
+        self.0
+            .borrow()
+            .coins
+            .iter()
+            .find(|(_, coin, _)| *coin == utxo_id)
+            .map(|(_, _, info)| CoinInfo {
+                owner: info.owner,
+                amount: info.amount,
+                asset_id: info.asset_id,
+            })
+            .ok_or_else(|| anyhow::anyhow!("Unknown coin {utxo_id:?}"))
+    }
+
+    fn message(&self, nonce: Nonce) -> anyhow::Result<MessageInfo> {
+        Err(anyhow::anyhow!("Unknown message {nonce}"))
+    }
+}
+
+/// Encodes the compressed `blocks` into the blobs of a single bundle.
+pub fn blobs_of_bundle(blocks: &[VersionedCompressedBlock]) -> Vec<Blob> {
+    let blocks = blocks
+        .iter()
+        .map(|block| postcard::to_allocvec(block).expect("Unable to encode the block"))
+        .collect();
+
+    let bundle = bundle::Bundle::V1(bundle::BundleV1 { blocks });
+    let bundle = bundle::Encoder::default()
+        .encode(bundle)
+        .expect("Unable to encode the bundle");
+
+    blob::Encoder::default()
+        .encode(&bundle, 0)
+        .expect("Unable to encode the blobs")
+        .into_iter()
+        .map(Blob::from)
+        .collect()
+}
@@ -0,0 +1,27 @@
+//! The encoding of the game inputs.
+//!
+//! Inputs are encoded with the legacy `bincode` configuration: little-endian integers
+//! of a fixed size. It produces the same bytes as `bincode` 1.x did,
+//! so the inputs encoded before the switch to `bincode` 2 still decode.
+
+use alloc::vec::Vec;
+use bincode::config;
+
+pub use bincode::error::{DecodeError, EncodeError};
+
+/// Encodes the `value` into a new buffer.
+pub fn serialize<T>(value: &T) -> Result<Vec<u8>, EncodeError>
+where
+    T: serde::Serialize + ?Sized,
+{
+    bincode::serde::encode_to_vec(value, config::legacy())
+}
+
+/// Decodes the value from the `bytes`, borrowing from them where the value allows it.
+/// Trailing bytes are ignored.
+pub fn deserialize<'a, T>(bytes: &'a [u8]) -> Result<T, DecodeError>
+where
+    T: serde::Deserialize<'a>,
+{
+    bincode::serde::borrow_decode_from_slice(bytes, config::legacy()).map(|(value, _)| value)
+}
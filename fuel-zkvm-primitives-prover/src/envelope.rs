@@ -0,0 +1,140 @@
+//! Self-describing envelope for the inputs of the proving games.
+//!
+//! An enveloped input is laid out as `MAGIC || game || version || payload`,
+//! where `game` is a single byte and `version` is a big-endian `u16`.
+//! Inputs produced before the envelope was introduced don't start with the `MAGIC`,
+//! they are treated as a bare payload of the [`LEGACY_VERSION`].
+
+use alloc::vec::Vec;
+
+/// Magic bytes that start every enveloped input.
+pub const MAGIC: [u8; 4] = *b"FZKV";
+
+/// The version of the inputs that were encoded without an envelope.
+pub const LEGACY_VERSION: u16 = 0;
+
+const HEADER_SIZE: usize = 7;
+
+/// The proving game an input is meant for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[repr(u8)]
+pub enum Game {
+    BlockExecution = 0,
+    BlockRangeExecution = 1,
+    Decompression = 2,
+    DecompressionExecution = 3,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, serde::Serialize, serde::Deserialize)]
+pub enum Error {
+    #[error("Envelope is too short to contain the header")]
+    Truncated,
+    #[error("Input is for the game {actual}, expected the game {expected:?}")]
+    WrongGame { expected: Game, actual: u8 },
+}
+
+/// The input split into its envelope header and the game specific payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Envelope<'a> {
+    pub game: Game,
+    pub version: u16,
+    pub payload: &'a [u8],
+}
+
+impl<'a> Envelope<'a> {
+    /// Opens the `bytes` meant for the `game`.
+    pub fn open(game: Game, bytes: &'a [u8]) -> Result<Self, Error> {
+        let Some(rest) = bytes.strip_prefix(&MAGIC) else {
+            return Ok(Self {
+                game,
+                version: LEGACY_VERSION,
+                payload: bytes,
+            });
+        };
+
+        let [actual, version_hi, version_lo, payload @ ..] = rest else {
+            return Err(Error::Truncated);
+        };
+
+        if *actual != game as u8 {
+            return Err(Error::WrongGame {
+                expected: game,
+                actual: *actual,
+            });
+        }
+
+        Ok(Self {
+            game,
+            version: u16::from_be_bytes([*version_hi, *version_lo]),
+            payload,
+        })
+    }
+
+    /// Encodes the envelope into bytes.
+    pub fn seal(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE.saturating_add(self.payload.len()));
+        bytes.extend_from_slice(&MAGIC);
+        bytes.push(self.game as u8);
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(self.payload);
+        bytes
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open__returns_sealed_envelope() {
+        let envelope = Envelope {
+            game: Game::Decompression,
+            version: 258,
+            payload: &[1, 2, 3],
+        };
+
+        let bytes = envelope.seal();
+
+        assert_eq!(Envelope::open(Game::Decompression, &bytes), Ok(envelope));
+    }
+
+    #[test]
+    fn open__treats_bytes_without_magic_as_legacy_payload() {
+        let bytes = [0, 0, 0, 0, 42];
+
+        let envelope = Envelope::open(Game::BlockExecution, &bytes).unwrap();
+
+        assert_eq!(envelope.version, LEGACY_VERSION);
+        assert_eq!(envelope.payload, &bytes[..]);
+    }
+
+    #[test]
+    fn open_fails__if_envelope_is_for_another_game() {
+        let bytes = Envelope {
+            game: Game::Decompression,
+            version: 1,
+            payload: &[],
+        }
+        .seal();
+
+        let result = Envelope::open(Game::BlockExecution, &bytes);
+
+        assert_eq!(
+            result,
+            Err(Error::WrongGame {
+                expected: Game::BlockExecution,
+                actual: Game::Decompression as u8,
+            })
+        );
+    }
+
+    #[test]
+    fn open_fails__if_header_is_truncated() {
+        let bytes = [MAGIC.as_slice(), &[0, 1]].concat();
+
+        let result = Envelope::open(Game::BlockExecution, &bytes);
+
+        assert_eq!(result, Err(Error::Truncated));
+    }
+}
@@ -1,3 +1,9 @@
 pub mod block_execution_game;
 
+pub mod block_range_execution_game;
+
+#[cfg(feature = "decompression")]
 pub mod decompression_game;
+
+#[cfg(feature = "decompression")]
+pub mod decompression_execution_game;
@@ -1,48 +1,167 @@
-use alloc::collections::BTreeMap;
+use crate::codec;
+use crate::envelope::{self, Envelope, Game, LEGACY_VERSION};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 use alloy_sol_types::{private::U256, sol};
-use core::cell::RefCell;
+use core::cell::{Cell, RefCell};
 use fuel_core_executor::{
     executor::{ExecutionInstance, ExecutionOptions},
     ports::RelayerPort,
 };
-use fuel_core_storage::transactional::{Changes, ConflictPolicy, StorageTransaction};
+use fuel_core_storage::{
+    column::Column,
+    iter::IterDirection,
+    kv_store::{KeyValueInspect, StorageColumn, WriteOperation},
+    transactional::Changes,
+};
 use fuel_core_types::{
     blockchain::{block::Block, primitives::DaBlockHeight},
     fuel_crypto,
-    services::{
-        executor::{Error as ExecutorError, Result as ExecutorResult},
-        relayer::Event,
-    },
+    fuel_merkle::binary::root_calculator::MerkleRootCalculator,
+    fuel_types::Bytes32,
+    services::{executor::Error as ExecutorError, relayer::Event},
 };
-use memory::PanicStorage;
-
-extern crate alloc;
+use memory::{witness_ref, WitnessStorage};
 
 sol! {
     /// The public values encoded as a struct that can be easily deserialized inside Solidity.
     struct PublicValuesStruct {
         uint256 input_hash;
         uint256 block_id;
+        uint256 state_changes_hash;
     }
 }
 
+/// This is the input to the block execution game.
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Input {
     pub block: Block,
+    /// The values read by the executor during validation of the `block`.
+    ///
+    /// The witness is trusted: Fuel block headers don't commit to a state root,
+    /// so there is nothing the recorded values could be proven against inside the guest.
+    /// The only per-contract roots are the `balance_root` and `state_root` of the contract
+    /// inputs and outputs, committed through the transactions root of the header.
+    /// fuel-core doesn't keep Merkle trees over contract balances and state anymore
+    /// and fills both roots with zeroes, so they can't anchor proofs of the
+    /// `ContractsAssets` and `ContractsState` reads either.
+    /// A proof produced by this game is only as trustworthy as the host that built the input.
     pub storage: Changes,
+    /// The iterations over the storage performed during validation of the `block`.
+    /// The entries they yielded are part of the `storage` witness.
+    pub ranges: Vec<RangeRead>,
+    pub relayer: Relayer,
+}
+
+/// Zero-copy counterpart of the [`Input`], decoded from the same bytes.
+/// The storage witness borrows keys and values from the input buffer instead of copying them.
+#[derive(serde::Deserialize)]
+pub struct InputRef<'a> {
+    pub block: Block,
+    #[serde(borrow)]
+    pub storage: WitnessRef<'a>,
+    pub ranges: Vec<RangeRead>,
     pub relayer: Relayer,
 }
 
+/// The storage witness borrowing keys and values from the buffer it was decoded from.
+/// It has the same encoding as `Changes`.
+pub type WitnessRef<'a> = BTreeMap<u32, BTreeMap<&'a [u8], WriteOperationRef<'a>>>;
+
+/// Borrowed counterpart of the `WriteOperation`, with the same encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+pub enum WriteOperationRef<'a> {
+    Insert(&'a [u8]),
+    Remove,
+}
+
+/// The iteration over a column of the storage, as requested from the `IterableStore`.
+///
+/// The guest only serves the iterations recorded by the host,
+/// yielding the entries of the storage witness that fall into the range.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RangeRead {
+    pub column: u32,
+    pub prefix: Option<Vec<u8>>,
+    pub start: Option<Vec<u8>>,
+    pub reverse: bool,
+}
+
+impl RangeRead {
+    pub fn new(
+        column: u32,
+        prefix: Option<&[u8]>,
+        start: Option<&[u8]>,
+        direction: IterDirection,
+    ) -> Self {
+        Self {
+            column,
+            prefix: prefix.map(<[u8]>::to_vec),
+            start: start.map(<[u8]>::to_vec),
+            reverse: matches!(direction, IterDirection::Reverse),
+        }
+    }
+
+    /// Returns whether the `key` is yielded by the iteration over this range.
+    pub fn contains(&self, key: &[u8]) -> bool {
+        let has_prefix = self
+            .prefix
+            .as_deref()
+            .map_or(true, |prefix| key.starts_with(prefix));
+
+        let after_start = match self.start.as_deref() {
+            None => true,
+            Some(start) if self.reverse => key <= start,
+            Some(start) => key >= start,
+        };
+
+        has_prefix && after_start
+    }
+}
+
 #[derive(Default, Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct Relayer(RefCell<BTreeMap<DaBlockHeight, Vec<Event>>>);
+pub struct Relayer {
+    events: RefCell<BTreeMap<DaBlockHeight, Vec<Event>>>,
+    /// The first DA height requested by the executor that is absent in the `events`.
+    #[serde(skip)]
+    missing_da_height: Cell<Option<DaBlockHeight>>,
+    /// Hashes of the events handed to the executor, in the order they were requested.
+    #[serde(skip)]
+    consumed_event_hashes: RefCell<Vec<Bytes32>>,
+}
 
 impl Relayer {
     pub fn new() -> Self {
-        Self(RefCell::new(BTreeMap::new()))
+        Self::default()
     }
 
     pub fn add_event(&self, da_block_height: DaBlockHeight, events: Vec<Event>) {
-        self.0.borrow_mut().insert(da_block_height, events);
+        self.events.borrow_mut().insert(da_block_height, events);
+    }
+
+    fn missing_da_height(&self) -> Option<DaBlockHeight> {
+        self.missing_da_height.get()
+    }
+
+    /// Returns the lowest DA height whose events weren't requested by the executor.
+    pub(crate) fn first_unused_da_height(&self) -> Option<DaBlockHeight> {
+        self.events.borrow().keys().next().copied()
+    }
+
+    /// Computes the event inbox root over the events consumed since the last call,
+    /// the same way the executor builds it for the block header.
+    pub(crate) fn take_event_inbox_root(&self) -> Bytes32 {
+        let mut root_calculator = MerkleRootCalculator::new();
+
+        for event_hash in self.consumed_event_hashes.take() {
+            root_calculator.push(event_hash.as_ref());
+        }
+
+        root_calculator.root().into()
     }
 }
 
@@ -52,50 +171,400 @@ impl RelayerPort for Relayer {
     }
 
     fn get_events(&self, da_block_height: &DaBlockHeight) -> anyhow::Result<Vec<Event>> {
-        self.0
-            .borrow_mut()
-            .remove(da_block_height)
-            .ok_or_else(|| anyhow::anyhow!("Not found"))
+        let events = self.events.borrow_mut().remove(da_block_height);
+
+        match &events {
+            Some(events) => self
+                .consumed_event_hashes
+                .borrow_mut()
+                .extend(events.iter().map(Event::hash)),
+            None if self.missing_da_height.get().is_none() => {
+                self.missing_da_height.set(Some(*da_block_height));
+            }
+            None => {}
+        }
+
+        events.ok_or_else(|| anyhow::anyhow!("Not found"))
+    }
+}
+
+// Allows several executions to share one relayer witness,
+// e.g. when validating a range of blocks.
+impl RelayerPort for &Relayer {
+    fn enabled(&self) -> bool {
+        (*self).enabled()
+    }
+
+    fn get_events(&self, da_block_height: &DaBlockHeight) -> anyhow::Result<Vec<Event>> {
+        (*self).get_events(da_block_height)
     }
 }
 
-mod memory {
+pub(crate) mod memory {
+    use super::{to_hex, RangeRead, WitnessRef, WriteOperationRef};
+    use alloc::{collections::BTreeSet, vec::Vec};
+    use core::cell::RefCell;
     use fuel_core_storage::{
         column::Column,
-        kv_store::{KeyValueInspect, Value},
-        Result as StorageResult,
+        iter::{BoxedIter, IntoBoxedIter, IterDirection, IterableStore},
+        kv_store::{KVItem, KeyItem, KeyValueInspect, StorageColumn, Value, WriteOperation},
+        transactional::Changes,
+        Error as StorageError, Result as StorageResult,
     };
 
-    #[derive(Default, Clone, Debug, PartialEq, Eq)]
-    pub struct PanicStorage;
+    /// Borrows the owned `changes` as the storage witness.
+    pub fn witness_ref(changes: &Changes) -> WitnessRef<'_> {
+        changes
+            .iter()
+            .map(|(column, tree)| {
+                let tree = tree
+                    .iter()
+                    .map(|(key, operation)| {
+                        let operation = match operation {
+                            WriteOperation::Insert(value) => WriteOperationRef::Insert(&value[..]),
+                            WriteOperation::Remove => WriteOperationRef::Remove,
+                        };
+                        (&key[..], operation)
+                    })
+                    .collect();
+                (*column, tree)
+            })
+            .collect()
+    }
+
+    /// The key requested by the executor that is absent in the storage witness.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct MissingKey {
+        pub column: Column,
+        pub key: Vec<u8>,
+    }
+
+    /// The storage that serves the values recorded in the storage witness.
+    /// Reading a key or iterating a range that the witness lacks fails.
+    /// The first such key or range is remembered,
+    /// so the game can report it after the execution fails.
+    #[derive(Debug)]
+    pub struct WitnessStorage<'a> {
+        witness: WitnessRef<'a>,
+        ranges: Vec<RangeRead>,
+        missing_key: RefCell<Option<MissingKey>>,
+        missing_range: RefCell<Option<RangeRead>>,
+        /// The witness keys read by the executor, `None` if the usage isn't tracked.
+        used_keys: Option<RefCell<BTreeSet<(u32, &'a [u8])>>>,
+        /// The indices of the `ranges` iterated by the executor, `None` if the usage isn't tracked.
+        used_ranges: Option<RefCell<BTreeSet<usize>>>,
+    }
+
+    impl<'a> WitnessStorage<'a> {
+        pub fn new(witness: WitnessRef<'a>) -> Self {
+            Self {
+                witness,
+                ranges: Vec::new(),
+                missing_key: Default::default(),
+                missing_range: Default::default(),
+                used_keys: None,
+                used_ranges: None,
+            }
+        }
+
+        /// Allows iterating over the recorded `ranges`.
+        pub fn with_ranges(self, ranges: Vec<RangeRead>) -> Self {
+            Self { ranges, ..self }
+        }
+
+        /// Creates the storage that also tracks which witness keys were read
+        /// and which ranges were iterated.
+        pub fn tracked(witness: WitnessRef<'a>) -> Self {
+            Self {
+                used_keys: Some(Default::default()),
+                used_ranges: Some(Default::default()),
+                ..Self::new(witness)
+            }
+        }
+
+        pub fn missing_key(&self) -> Option<MissingKey> {
+            self.missing_key.borrow().clone()
+        }
+
+        pub fn missing_range(&self) -> Option<RangeRead> {
+            self.missing_range.borrow().clone()
+        }
+
+        /// Returns the witness entries yielded by the iteration over the recorded range,
+        /// in the order of the iteration. Removed keys are skipped.
+        fn range_entries(
+            &self,
+            column: Column,
+            prefix: Option<&[u8]>,
+            start: Option<&[u8]>,
+            direction: IterDirection,
+        ) -> StorageResult<Vec<(&'a [u8], &'a [u8])>> {
+            let range = RangeRead::new(column.id(), prefix, start, direction);
+
+            let Some(index) = self.ranges.iter().position(|recorded| *recorded == range) else {
+                let mut missing_range = self.missing_range.borrow_mut();
+                if missing_range.is_none() {
+                    *missing_range = Some(range);
+                }
+
+                return Err(StorageError::Other(anyhow::anyhow!(
+                    "The range is missing in the storage witness of column {}",
+                    column.name()
+                )));
+            };
+
+            if let Some(used_ranges) = &self.used_ranges {
+                used_ranges.borrow_mut().insert(index);
+            }
+
+            let mut entries = self
+                .witness
+                .get(&column.id())
+                .into_iter()
+                .flatten()
+                .filter(|(key, _)| range.contains(key))
+                .filter_map(|(key, operation)| match operation {
+                    WriteOperationRef::Insert(value) => Some((*key, *value)),
+                    WriteOperationRef::Remove => None,
+                })
+                .collect::<Vec<_>>();
+
+            if range.reverse {
+                entries.reverse();
+            }
+
+            if let Some(used_keys) = &self.used_keys {
+                let mut used_keys = used_keys.borrow_mut();
+                for (key, _) in &entries {
+                    used_keys.insert((column.id(), *key));
+                }
+            }
+
+            Ok(entries)
+        }
 
-    impl KeyValueInspect for PanicStorage {
+        /// Returns the first witness key, ordered by column and key, that wasn't read.
+        /// Always returns `None` if the usage isn't tracked.
+        pub fn first_unused_key(&self) -> Option<(u32, &'a [u8])> {
+            let used_keys = self.used_keys.as_ref()?.borrow();
+
+            self.witness
+                .iter()
+                .flat_map(|(column, tree)| tree.keys().map(move |key| (*column, *key)))
+                .find(|entry| !used_keys.contains(entry))
+        }
+
+        /// Returns the first recorded range that wasn't iterated.
+        /// Always returns `None` if the usage isn't tracked.
+        pub fn first_unused_range(&self) -> Option<&RangeRead> {
+            let used_ranges = self.used_ranges.as_ref()?.borrow();
+
+            self.ranges
+                .iter()
+                .enumerate()
+                .find(|(index, _)| !used_ranges.contains(index))
+                .map(|(_, range)| range)
+        }
+
+        /// Returns the recorded ranges iterated so far, in the recorded order,
+        /// always empty if the usage isn't tracked.
+        pub fn used_ranges(&self) -> Vec<RangeRead> {
+            let Some(used_ranges) = &self.used_ranges else {
+                return Vec::new();
+            };
+            let used_ranges = used_ranges.borrow();
+
+            self.ranges
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| used_ranges.contains(index))
+                .map(|(_, range)| range.clone())
+                .collect()
+        }
+
+        /// Returns the witness keys read so far, always empty if the usage isn't tracked.
+        pub fn used_keys(&self) -> BTreeSet<(u32, &'a [u8])> {
+            self.used_keys
+                .as_ref()
+                .map(|used_keys| used_keys.borrow().clone())
+                .unwrap_or_default()
+        }
+    }
+
+    impl KeyValueInspect for WitnessStorage<'_> {
         type Column = Column;
 
-        fn get(&self, _: &[u8], _: Self::Column) -> StorageResult<Option<Value>> {
-            panic!(
-                "The panic storage does not support reading. \
-                All data should be provided via the `Input` struct.\
-                If the execution hits this panic, it means that the fuel-zkvm-primitives-prover \
-                is not set up correctly. Or the execution is impossible."
-            )
+        fn get(&self, key: &[u8], column: Self::Column) -> StorageResult<Option<Value>> {
+            let Some((witness_key, operation)) = self
+                .witness
+                .get(&column.id())
+                .and_then(|tree| tree.get_key_value(key))
+            else {
+                let mut missing_key = self.missing_key.borrow_mut();
+                if missing_key.is_none() {
+                    *missing_key = Some(MissingKey {
+                        column,
+                        key: key.to_vec(),
+                    });
+                }
+
+                return Err(StorageError::Other(anyhow::anyhow!(
+                    "The key 0x{} is missing in the storage witness of column {}",
+                    to_hex(key),
+                    column.name()
+                )));
+            };
+
+            if let Some(used_keys) = &self.used_keys {
+                used_keys.borrow_mut().insert((column.id(), *witness_key));
+            }
+
+            // The value is copied once, straight from the input buffer,
+            // and only when the executor actually reads it.
+            let value = match operation {
+                WriteOperationRef::Insert(value) => Some(Value::from(*value)),
+                WriteOperationRef::Remove => None,
+            };
+
+            Ok(value)
+        }
+    }
+
+    impl IterableStore for WitnessStorage<'_> {
+        fn iter_store(
+            &self,
+            column: Self::Column,
+            prefix: Option<&[u8]>,
+            start: Option<&[u8]>,
+            direction: IterDirection,
+        ) -> BoxedIter<KVItem> {
+            match self.range_entries(column, prefix, start, direction) {
+                Ok(entries) => entries
+                    .into_iter()
+                    .map(|(key, value)| Ok((key.to_vec(), Value::from(value))))
+                    .into_boxed(),
+                Err(e) => core::iter::once(Err(e)).into_boxed(),
+            }
+        }
+
+        fn iter_store_keys(
+            &self,
+            column: Self::Column,
+            prefix: Option<&[u8]>,
+            start: Option<&[u8]>,
+            direction: IterDirection,
+        ) -> BoxedIter<KeyItem> {
+            match self.range_entries(column, prefix, start, direction) {
+                Ok(entries) => entries
+                    .into_iter()
+                    .map(|(key, _)| Ok(key.to_vec()))
+                    .into_boxed(),
+                Err(e) => core::iter::once(Err(e)).into_boxed(),
+            }
         }
     }
 }
 
-pub fn prove(input_bytes: &[u8]) -> ExecutorResult<PublicValuesStruct> {
-    let input: Input = bincode::deserialize_from(input_bytes)
-        .map_err(|e| ExecutorError::Other(format!("Unable to decode the input {e}")))?;
+/// The version of the `Input` encoding produced by [`encode_input`].
+pub const INPUT_VERSION: u16 = 2;
 
-    let Input {
-        block,
-        storage,
-        relayer,
-    } = input;
+/// The version of the `Input` encoding before the range reads were recorded.
+const INPUT_VERSION_WITHOUT_RANGES: u16 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid input envelope: {0}")]
+    InvalidEnvelope(envelope::Error),
+    #[error("Unsupported input version {0}")]
+    UnsupportedInputVersion(u16),
+    #[error("Failed to decode the input: {0}")]
+    FailedDecodeInput(String),
+    #[error("Relayer events at DA height {da_height} are missing in the input")]
+    MissingRelayerEvents { da_height: u64 },
+    #[error("Storage witness is missing key 0x{key} in column {column}")]
+    MissingStorageWitness { column: String, key: String },
+    #[error("Storage witness is missing the range read {range:?}")]
+    MissingRangeWitness { range: RangeRead },
+    #[error("Block validation failed: {0}")]
+    FailedValidation(ExecutorError),
+    #[error("Relayer events don't match the event inbox root: expected {expected}, got {actual}")]
+    EventInboxRootMismatch { expected: Bytes32, actual: Bytes32 },
+    #[error("Storage witness key 0x{key} in column {column} wasn't used")]
+    UnusedStorageWitness { column: u32, key: String },
+    #[error("Storage witness range read {range:?} wasn't used")]
+    UnusedRangeWitness { range: RangeRead },
+    #[error("Relayer events at DA height {da_height} weren't used")]
+    UnusedRelayerEvents { da_height: u64 },
+}
+
+pub type BlockExecutionGameResult<T> = core::result::Result<T, Error>;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
 
-    let panic_storage = PanicStorage;
-    let storage = StorageTransaction::transaction(panic_storage, ConflictPolicy::Fail, storage);
+/// Computes the canonical digest of the state changes produced by the execution.
+///
+/// Columns are hashed in ascending order of their ids and keys in ascending byte order,
+/// so the digest doesn't depend on the iteration order of the `Changes` map.
+/// Every entry is hashed as `column id || key length || key || operation`,
+/// where the operation is `0 || value length || value` for inserts and `1` for removals.
+/// All integers are big-endian, lengths are `u64`.
+pub fn state_changes_hash(changes: &Changes) -> Bytes32 {
+    let mut columns = changes.iter().collect::<Vec<_>>();
+    columns.sort_unstable_by_key(|(column, _)| **column);
 
+    let mut hasher = fuel_crypto::Hasher::default();
+
+    for (column, operations) in columns {
+        for (key, operation) in operations {
+            hasher.input(column.to_be_bytes());
+            hasher.input((key.len() as u64).to_be_bytes());
+            hasher.input(&key[..]);
+
+            match operation {
+                WriteOperation::Insert(value) => {
+                    hasher.input([0u8]);
+                    hasher.input((value.len() as u64).to_be_bytes());
+                    hasher.input(&value[..]);
+                }
+                WriteOperation::Remove => {
+                    hasher.input([1u8]);
+                }
+            }
+        }
+    }
+
+    hasher.finalize()
+}
+
+/// Returns the error reporting the first key or range the `witness` was asked for and lacks.
+pub(crate) fn missing_witness(witness: &WitnessStorage) -> Option<Error> {
+    if let Some(missing_key) = witness.missing_key() {
+        Some(Error::MissingStorageWitness {
+            column: missing_key.column.name(),
+            key: to_hex(&missing_key.key),
+        })
+    } else {
+        witness
+            .missing_range()
+            .map(|range| Error::MissingRangeWitness { range })
+    }
+}
+
+/// Validates the `block` on top of the `storage`, returning the state changes of the execution.
+///
+/// The `storage` serves the values of the `witness`, possibly with the writes of the previous
+/// blocks layered on top of it. The keys and ranges the `witness` lacks are reported as such.
+pub(crate) fn validate_block<S>(
+    block: &Block,
+    storage: S,
+    witness: &WitnessStorage,
+    relayer: &Relayer,
+) -> BlockExecutionGameResult<Changes>
+where
+    S: KeyValueInspect<Column = Column>,
+{
     let validator = ExecutionInstance::new(
         relayer,
         storage,
@@ -105,16 +574,532 @@ pub fn prove(input_bytes: &[u8]) -> ExecutorResult<PublicValuesStruct> {
         },
     );
 
-    // We don't need artifacts from validation
-    let _ = validator.validate_without_commit(&block)?;
+    // We only need the state changes from the validation artifacts
+    let changes = validator
+        .validate_without_commit(block)
+        .map_err(|e| {
+            if let Some(da_height) = relayer.missing_da_height() {
+                Error::MissingRelayerEvents {
+                    da_height: da_height.0,
+                }
+            } else if let Some(error) = missing_witness(witness) {
+                error
+            } else {
+                Error::FailedValidation(e)
+            }
+        })?
+        .into_changes();
+
+    // The root covers the events of the DA heights the executor asked for,
+    // so missing, extra or reordered events at those heights change it.
+    // Events at the heights it never asked for don't, the callers reject them separately.
+    let expected = block.header().application().event_inbox_root;
+    let actual = relayer.take_event_inbox_root();
+    if expected != actual {
+        return Err(Error::EventInboxRootMismatch { expected, actual });
+    }
+
+    Ok(changes)
+}
+
+/// Validates the `block` on top of the witnesses, returning the state changes of the execution.
+///
+/// Fails if the relayer witness contains events of the DA heights the executor didn't ask for,
+/// as the event inbox root doesn't commit to them.
+fn execute(
+    block: &Block,
+    storage: &WitnessStorage,
+    relayer: &Relayer,
+) -> BlockExecutionGameResult<Changes> {
+    let changes = validate_block(block, storage, storage, relayer)?;
+
+    if let Some(da_height) = relayer.first_unused_da_height() {
+        return Err(Error::UnusedRelayerEvents {
+            da_height: da_height.0,
+        });
+    }
+
+    Ok(changes)
+}
+
+pub fn prove(input_bytes: &[u8]) -> BlockExecutionGameResult<PublicValuesStruct> {
+    prove_with(input_bytes, false)
+}
+
+/// Same as [`prove`], but also fails if the input contains storage entries or range reads
+/// that weren't used during the validation, so the `input_hash` can't commit to padding.
+/// Hosts can build such inputs with [`minimize_input`].
+pub fn prove_strict(input_bytes: &[u8]) -> BlockExecutionGameResult<PublicValuesStruct> {
+    prove_with(input_bytes, true)
+}
+
+/// Encodes the `input` into an envelope of the current [`INPUT_VERSION`].
+pub fn encode_input(input: &Input) -> Result<Vec<u8>, codec::EncodeError> {
+    let payload = codec::serialize(input)?;
+
+    Ok(Envelope {
+        game: Game::BlockExecution,
+        version: INPUT_VERSION,
+        payload: &payload,
+    }
+    .seal())
+}
+
+/// Returns the version and the payload of enveloped or legacy `input_bytes`.
+fn open_input(input_bytes: &[u8]) -> BlockExecutionGameResult<(u16, &[u8])> {
+    let envelope =
+        Envelope::open(Game::BlockExecution, input_bytes).map_err(Error::InvalidEnvelope)?;
+
+    match envelope.version {
+        LEGACY_VERSION | INPUT_VERSION_WITHOUT_RANGES | INPUT_VERSION => {
+            Ok((envelope.version, envelope.payload))
+        }
+        version => Err(Error::UnsupportedInputVersion(version)),
+    }
+}
+
+/// Decodes the `Input` from enveloped or legacy `input_bytes`.
+pub fn decode_input(input_bytes: &[u8]) -> BlockExecutionGameResult<Input> {
+    let (version, payload) = open_input(input_bytes)?;
+
+    let input = if version == INPUT_VERSION {
+        codec::deserialize(payload)
+    } else {
+        // The legacy inputs share the payload layout with the first version,
+        // which lacks the range reads.
+        codec::deserialize(payload).map(|(block, storage, relayer)| Input {
+            block,
+            storage,
+            ranges: Vec::new(),
+            relayer,
+        })
+    };
+
+    input.map_err(|e| Error::FailedDecodeInput(e.to_string()))
+}
+
+/// Decodes the `InputRef` from enveloped or legacy `input_bytes` without copying the witness.
+pub fn decode_input_ref(input_bytes: &[u8]) -> BlockExecutionGameResult<InputRef<'_>> {
+    let (version, payload) = open_input(input_bytes)?;
+
+    let input = if version == INPUT_VERSION {
+        codec::deserialize(payload)
+    } else {
+        codec::deserialize(payload).map(|(block, storage, relayer)| InputRef {
+            block,
+            storage,
+            ranges: Vec::new(),
+            relayer,
+        })
+    };
+
+    input.map_err(|e| Error::FailedDecodeInput(e.to_string()))
+}
+
+fn prove_with(input_bytes: &[u8], strict: bool) -> BlockExecutionGameResult<PublicValuesStruct> {
+    let input = decode_input_ref(input_bytes)?;
+
+    let InputRef {
+        block,
+        storage,
+        ranges,
+        relayer,
+    } = input;
+
+    let storage = if strict {
+        WitnessStorage::tracked(storage)
+    } else {
+        WitnessStorage::new(storage)
+    }
+    .with_ranges(ranges);
+
+    let changes = execute(&block, &storage, &relayer)?;
+
+    if strict {
+        if let Some((column, key)) = storage.first_unused_key() {
+            return Err(Error::UnusedStorageWitness {
+                column,
+                key: to_hex(key),
+            });
+        }
+
+        if let Some(range) = storage.first_unused_range() {
+            return Err(Error::UnusedRangeWitness {
+                range: range.clone(),
+            });
+        }
+    }
 
     // Prepare return values
     let input_hash = fuel_crypto::Hasher::hash(input_bytes);
     let block_id = block.header().id();
+    let state_changes_hash = state_changes_hash(&changes);
 
     let proof = PublicValuesStruct {
         input_hash: U256::from_be_bytes(input_hash.into()),
         block_id: U256::from_be_bytes(block_id.into()),
+        state_changes_hash: U256::from_be_bytes(state_changes_hash.into()),
     };
     Ok(proof)
 }
+
+/// Prunes the `input` down to the storage keys, range reads and relayer events
+/// used during the validation of its block, so it can be proven with [`prove_strict`].
+pub fn minimize_input(input: Input) -> BlockExecutionGameResult<Input> {
+    let Input {
+        block,
+        mut storage,
+        ranges,
+        relayer,
+    } = input;
+
+    let events = relayer.events.borrow().clone();
+
+    let (used_keys, ranges) = {
+        let witness = WitnessStorage::tracked(witness_ref(&storage)).with_ranges(ranges);
+        // The unused relayer events are pruned below instead of being rejected.
+        validate_block(&block, &witness, &witness, &relayer)?;
+
+        let used_keys = witness
+            .used_keys()
+            .into_iter()
+            .map(|(column, key)| (column, key.to_vec()))
+            .collect::<BTreeSet<_>>();
+
+        (used_keys, witness.used_ranges())
+    };
+
+    for (column, tree) in storage.iter_mut() {
+        tree.retain(|key, _| used_keys.contains(&(*column, key.to_vec())));
+    }
+    storage.retain(|_, tree| !tree.is_empty());
+
+    let minimal_relayer = Relayer::new();
+    let unused_events = relayer.events.borrow();
+    for (da_height, events) in events {
+        if !unused_events.contains_key(&da_height) {
+            minimal_relayer.add_event(da_height, events);
+        }
+    }
+
+    Ok(Input {
+        block,
+        storage,
+        ranges,
+        relayer: minimal_relayer,
+    })
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn changes(entries: &[(u32, &[u8], Option<&[u8]>)]) -> Changes {
+        let mut changes = Changes::default();
+
+        for (column, key, value) in entries {
+            let operation = match value {
+                Some(value) => WriteOperation::Insert(value.to_vec().into()),
+                None => WriteOperation::Remove,
+            };
+            changes
+                .entry(*column)
+                .or_default()
+                .insert(key.to_vec().into(), operation);
+        }
+
+        changes
+    }
+
+    #[test]
+    fn prove_fails__if_bad_input_provided() {
+        #[derive(serde::Serialize)]
+        struct BadInput {
+            foo: u32,
+        }
+
+        let bad_input = BadInput { foo: 10 };
+
+        let input_bytes = codec::serialize(&bad_input).unwrap();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(result, Err(Error::FailedDecodeInput(_))));
+    }
+
+    #[test]
+    fn prove_fails__if_unsupported_input_version_provided() {
+        let input_bytes = Envelope {
+            game: Game::BlockExecution,
+            version: u16::MAX,
+            payload: &[],
+        }
+        .seal();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedInputVersion(version)) if version == u16::MAX
+        ));
+    }
+
+    #[test]
+    fn relayer__event_inbox_root_is_empty_root_without_events() {
+        let relayer = Relayer::new();
+
+        assert_eq!(
+            relayer.take_event_inbox_root(),
+            Bytes32::from(MerkleRootCalculator::new().root())
+        );
+    }
+
+    #[test]
+    fn relayer__event_inbox_root_depends_on_event_order() {
+        use fuel_core_types::{entities::relayer::message::Message, fuel_types::Nonce};
+
+        let mut first = Message::default();
+        first.set_nonce(Nonce::new([1; 32]));
+        let mut second = Message::default();
+        second.set_nonce(Nonce::new([2; 32]));
+
+        let events = vec![Event::Message(first), Event::Message(second)];
+        let reordered = events.iter().rev().cloned().collect::<Vec<_>>();
+
+        let relayer = Relayer::new();
+        relayer.add_event(1u64.into(), events);
+        relayer.add_event(2u64.into(), reordered);
+
+        relayer.get_events(&1u64.into()).unwrap();
+        let root = relayer.take_event_inbox_root();
+
+        relayer.get_events(&2u64.into()).unwrap();
+        let reordered_root = relayer.take_event_inbox_root();
+
+        assert_ne!(root, reordered_root);
+    }
+
+    #[test]
+    fn witness_storage__reports_first_missing_key() {
+        use fuel_core_storage::{column::Column, kv_store::KeyValueInspect};
+
+        let witness = Changes::default();
+        let storage = WitnessStorage::new(witness_ref(&witness));
+
+        let first = storage.get(&[0xab, 0x01], Column::Metadata);
+        let _ = storage.get(&[0xcd], Column::ContractsRawCode);
+
+        assert!(first.is_err());
+        assert_eq!(
+            storage.missing_key(),
+            Some(memory::MissingKey {
+                column: Column::Metadata,
+                key: vec![0xab, 0x01],
+            })
+        );
+    }
+
+    #[test]
+    fn witness_storage__serves_witness_values() {
+        use fuel_core_storage::{column::Column, kv_store::KeyValueInspect};
+
+        let column = Column::Metadata.id();
+        let witness = changes(&[(column, b"a", Some(b"1")), (column, b"b", None)]);
+        let storage = WitnessStorage::new(witness_ref(&witness));
+
+        let inserted = storage.get(b"a", Column::Metadata).unwrap();
+        let removed = storage.get(b"b", Column::Metadata).unwrap();
+
+        assert_eq!(inserted.as_deref().map(|value| &value[..]), Some(&b"1"[..]));
+        assert!(removed.is_none());
+        assert_eq!(storage.missing_key(), None);
+    }
+
+    #[test]
+    fn witness_storage__tracks_unused_keys() {
+        use fuel_core_storage::{column::Column, kv_store::KeyValueInspect};
+
+        let column = Column::Metadata.id();
+        let witness = changes(&[(column, b"a", Some(b"1")), (column, b"b", Some(b"2"))]);
+        let storage = WitnessStorage::tracked(witness_ref(&witness));
+
+        storage.get(b"b", Column::Metadata).unwrap();
+
+        assert_eq!(storage.first_unused_key(), Some((column, &b"a"[..])));
+        assert_eq!(
+            storage.used_keys().into_iter().collect::<Vec<_>>(),
+            vec![(column, &b"b"[..])]
+        );
+    }
+
+    #[test]
+    fn witness_storage__tracks_unused_ranges() {
+        use fuel_core_storage::{column::Column, iter::IterableStore};
+
+        let column = Column::Metadata.id();
+        let witness = changes(&[(column, b"a", Some(b"1"))]);
+        let ranges = vec![
+            RangeRead::new(column, None, None, IterDirection::Forward),
+            RangeRead::new(column, None, None, IterDirection::Reverse),
+        ];
+        let storage = WitnessStorage::tracked(witness_ref(&witness)).with_ranges(ranges.clone());
+
+        storage
+            .iter_store_keys(Column::Metadata, None, None, IterDirection::Reverse)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(storage.first_unused_range(), Some(&ranges[0]));
+        assert_eq!(storage.used_ranges(), vec![ranges[1].clone()]);
+        assert_eq!(storage.first_unused_key(), None);
+    }
+
+    #[test]
+    fn decode_input_ref__borrows_the_same_witness_as_decode_input() {
+        let input = Input {
+            block: Block::default(),
+            storage: changes(&[(1, b"a", Some(b"1")), (2, b"b", None), (2, b"c", Some(b""))]),
+            ranges: vec![RangeRead::new(2, None, None, IterDirection::Forward)],
+            relayer: Relayer::new(),
+        };
+        let input_bytes = encode_input(&input).unwrap();
+
+        let decoded = decode_input(&input_bytes).unwrap();
+        let decoded_ref = decode_input_ref(&input_bytes).unwrap();
+
+        assert_eq!(decoded_ref.storage, witness_ref(&decoded.storage));
+        assert_eq!(decoded_ref.ranges, decoded.ranges);
+        assert_eq!(decoded_ref.block.header().id(), decoded.block.header().id());
+    }
+
+    #[test]
+    fn relayer__reports_first_unused_da_height() {
+        let relayer = Relayer::new();
+        relayer.add_event(1u64.into(), vec![]);
+        relayer.add_event(2u64.into(), vec![]);
+
+        relayer.get_events(&1u64.into()).unwrap();
+
+        assert_eq!(relayer.first_unused_da_height(), Some(2u64.into()));
+    }
+
+    #[test]
+    fn state_changes_hash__does_not_depend_on_insertion_order() {
+        let a = changes(&[
+            (1, b"a", Some(b"1")),
+            (7, b"b", None),
+            (3, b"c", Some(b"2")),
+        ]);
+        let b = changes(&[
+            (3, b"c", Some(b"2")),
+            (1, b"a", Some(b"1")),
+            (7, b"b", None),
+        ]);
+
+        assert_eq!(state_changes_hash(&a), state_changes_hash(&b));
+    }
+
+    #[test]
+    fn state_changes_hash__distinguishes_insert_and_remove() {
+        let insert = changes(&[(1, b"a", Some(b""))]);
+        let remove = changes(&[(1, b"a", None)]);
+
+        assert_ne!(state_changes_hash(&insert), state_changes_hash(&remove));
+    }
+
+    #[test]
+    fn state_changes_hash__distinguishes_key_and_value_boundaries() {
+        let a = changes(&[(1, b"ab", Some(b"c"))]);
+        let b = changes(&[(1, b"a", Some(b"bc"))]);
+
+        assert_ne!(state_changes_hash(&a), state_changes_hash(&b));
+    }
+
+    #[test]
+    fn decode_input__reads_inputs_without_range_reads() {
+        let storage = changes(&[(1, b"a", Some(b"1"))]);
+        let payload = codec::serialize(&(Block::default(), &storage, Relayer::new())).unwrap();
+        let input_bytes = Envelope {
+            game: Game::BlockExecution,
+            version: INPUT_VERSION_WITHOUT_RANGES,
+            payload: &payload,
+        }
+        .seal();
+
+        let decoded = decode_input(&input_bytes).unwrap();
+        let decoded_ref = decode_input_ref(&input_bytes).unwrap();
+
+        assert!(decoded.ranges.is_empty());
+        assert!(decoded_ref.ranges.is_empty());
+        assert_eq!(decoded_ref.storage, witness_ref(&storage));
+    }
+
+    #[test]
+    fn witness_storage__serves_recorded_range() {
+        use fuel_core_storage::{column::Column, iter::IterableStore};
+
+        let column = Column::Metadata.id();
+        let witness = changes(&[
+            (column, b"a1", Some(b"1")),
+            (column, b"a2", None),
+            (column, b"a3", Some(b"3")),
+            (column, b"a4", Some(b"4")),
+            (column, b"b1", Some(b"5")),
+        ]);
+        let ranges = vec![
+            RangeRead::new(column, Some(b"a"), Some(b"a2"), IterDirection::Forward),
+            RangeRead::new(column, Some(b"a"), None, IterDirection::Reverse),
+        ];
+        let storage = WitnessStorage::new(witness_ref(&witness)).with_ranges(ranges);
+
+        let forward = storage
+            .iter_store_keys(
+                Column::Metadata,
+                Some(b"a"),
+                Some(b"a2"),
+                IterDirection::Forward,
+            )
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let reverse = storage
+            .iter_store(Column::Metadata, Some(b"a"), None, IterDirection::Reverse)
+            .map(|entry| entry.map(|(key, value)| (key, value.to_vec())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(forward, vec![b"a3".to_vec(), b"a4".to_vec()]);
+        assert_eq!(
+            reverse,
+            vec![
+                (b"a4".to_vec(), b"4".to_vec()),
+                (b"a3".to_vec(), b"3".to_vec()),
+                (b"a1".to_vec(), b"1".to_vec()),
+            ]
+        );
+    }
+
+    #[test]
+    fn witness_storage__reports_missing_range() {
+        use fuel_core_storage::{column::Column, iter::IterableStore};
+
+        let witness = changes(&[(Column::Metadata.id(), b"a", Some(b"1"))]);
+        let storage = WitnessStorage::new(witness_ref(&witness));
+
+        let result = storage
+            .iter_store_keys(Column::Metadata, None, None, IterDirection::Forward)
+            .collect::<Result<Vec<_>, _>>();
+
+        assert!(result.is_err());
+        assert_eq!(
+            storage.missing_range(),
+            Some(RangeRead::new(
+                Column::Metadata.id(),
+                None,
+                None,
+                IterDirection::Forward
+            ))
+        );
+    }
+}
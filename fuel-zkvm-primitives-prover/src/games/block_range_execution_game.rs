@@ -0,0 +1,417 @@
+use crate::codec;
+use crate::envelope::{self, Envelope, Game, LEGACY_VERSION};
+use crate::games::block_execution_game::{
+    self,
+    memory::{witness_ref, WitnessStorage},
+    missing_witness, validate_block, RangeRead, Relayer,
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use alloy_sol_types::{private::U256, sol};
+use fuel_core_storage::{
+    column::Column,
+    kv_store::KeyValueInspect,
+    tables::{ConsensusParametersVersions, FuelBlocks},
+    transactional::{Changes, ConflictPolicy, Modifiable, StorageTransaction},
+    StorageAsMut, StorageAsRef,
+};
+use fuel_core_types::{
+    blockchain::block::Block,
+    fuel_crypto,
+    fuel_types::{Bytes32, ChainId},
+};
+
+sol! {
+    /// The public values encoded as a struct that can be easily deserialized inside Solidity.
+    struct PublicValuesStruct {
+        uint256 input_hash;
+        uint256 first_block_id;
+        uint256 first_block_height;
+        uint256 last_block_id;
+        uint256 last_block_height;
+    }
+}
+
+/// This is the input to the block range execution game.
+/// The blocks are validated one after another on top of a single storage witness,
+/// so the writes of each block are visible to the blocks that follow it.
+///
+/// The `storage` witness must be recorded by executing the same blocks in the same order,
+/// importing each block into the storage before executing the next one.
+/// Like in the block execution game, the witness is trusted.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Input {
+    pub blocks: Vec<Block>,
+    pub storage: Changes,
+    /// The iterations over the storage witness performed during validation of the `blocks`.
+    pub ranges: Vec<RangeRead>,
+    pub relayer: Relayer,
+}
+
+/// The version of the `Input` encoding produced by [`encode_input`].
+pub const INPUT_VERSION: u16 = 2;
+
+/// The version of the `Input` encoding before the range reads were recorded.
+const INPUT_VERSION_WITHOUT_RANGES: u16 = 1;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid input envelope: {0}")]
+    InvalidEnvelope(envelope::Error),
+    #[error("Unsupported input version {0}")]
+    UnsupportedInputVersion(u16),
+    #[error("Failed to decode the input: {0}")]
+    FailedDecodeInput(String),
+    #[error("The input doesn't contain any blocks")]
+    NoBlocks,
+    #[error("Block at height {height} doesn't follow the block at height {previous_height}")]
+    NonContiguousBlocks { previous_height: u32, height: u32 },
+    #[error("Failed to execute the block at height {block_height}: {error}")]
+    FailedExecution {
+        block_height: u32,
+        error: block_execution_game::Error,
+    },
+    #[error(
+        "Consensus parameters {version} of the block at height {block_height} \
+        are missing in the input"
+    )]
+    MissingConsensusParameters { block_height: u32, version: u32 },
+    #[error("Relayer events at DA height {da_height} weren't used by any block")]
+    UnusedRelayerEvents { da_height: u64 },
+    #[error("Failed to import the block at height {block_height}: {reason}")]
+    FailedImportBlock { block_height: u32, reason: String },
+    #[error("Failed to read the root of the blocks preceding height {block_height}: {reason}")]
+    FailedReadPrevRoot { block_height: u32, reason: String },
+    #[error(
+        "Previous root of the block at height {block_height} doesn't match \
+        the root of the preceding blocks: expected {expected}, got {actual}"
+    )]
+    PrevRootMismatch {
+        block_height: u32,
+        expected: Bytes32,
+        actual: Bytes32,
+    },
+}
+
+pub type BlockRangeExecutionGameResult<T> = core::result::Result<T, Error>;
+
+/// Encodes the `input` into an envelope of the current [`INPUT_VERSION`].
+pub fn encode_input(input: &Input) -> Result<Vec<u8>, codec::EncodeError> {
+    let payload = codec::serialize(input)?;
+
+    Ok(Envelope {
+        game: Game::BlockRangeExecution,
+        version: INPUT_VERSION,
+        payload: &payload,
+    }
+    .seal())
+}
+
+/// Commits the `changes` of the validated block and inserts the block into the storage,
+/// the same way the block importer does, so the next block in the range can find its predecessor.
+///
+/// Hosts recording the witness of a block range must import the blocks with this function,
+/// so the witness serves the same reads as the game does.
+/// Returns the chain id the block was imported under.
+pub fn import_block<S>(
+    storage: &mut StorageTransaction<S>,
+    block: &Block,
+    changes: Changes,
+) -> BlockRangeExecutionGameResult<ChainId>
+where
+    S: KeyValueInspect<Column = Column>,
+{
+    let block_height = u32::from(*block.header().height());
+    let failed_import = |e: fuel_core_storage::Error| Error::FailedImportBlock {
+        block_height,
+        reason: e.to_string(),
+    };
+
+    storage.commit_changes(changes).map_err(failed_import)?;
+
+    let version = block.header().application().consensus_parameters_version;
+
+    let chain_id = storage
+        .storage_as_ref::<ConsensusParametersVersions>()
+        .get(&version)
+        .map_err(failed_import)?
+        .ok_or(Error::MissingConsensusParameters {
+            block_height,
+            version,
+        })?
+        .chain_id();
+
+    storage
+        .storage_as_mut::<FuelBlocks>()
+        .insert(block.header().height(), &block.compress(&chain_id))
+        .map_err(failed_import)?;
+
+    Ok(chain_id)
+}
+
+/// Checks that the `prev_root` of the `block` header is the Merkle root of the blocks
+/// preceding it in the `storage`, the same way the block verifier of fuel-core does.
+///
+/// Hosts recording the witness of a block range must check every block with this function
+/// before validating it, so the witness serves the same reads as the game does.
+pub fn check_prev_root<S>(
+    storage: &StorageTransaction<S>,
+    block: &Block,
+) -> BlockRangeExecutionGameResult<()>
+where
+    S: KeyValueInspect<Column = Column>,
+{
+    let height = *block.header().height();
+    let block_height = u32::from(height);
+
+    let previous_height = height.pred().ok_or_else(|| Error::FailedReadPrevRoot {
+        block_height,
+        reason: "The genesis block has no preceding blocks".to_string(),
+    })?;
+
+    let expected: Bytes32 = storage
+        .storage_as_ref::<FuelBlocks>()
+        .root(&previous_height)
+        .map_err(|e| Error::FailedReadPrevRoot {
+            block_height,
+            reason: e.to_string(),
+        })?
+        .into();
+
+    let actual = *block.header().prev_root();
+    if expected != actual {
+        return Err(Error::PrevRootMismatch {
+            block_height,
+            expected,
+            actual,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that every block of the non-empty `blocks` follows the previous one.
+fn check_contiguous(blocks: &[Block]) -> BlockRangeExecutionGameResult<()> {
+    if blocks.is_empty() {
+        return Err(Error::NoBlocks);
+    }
+
+    for pair in blocks.windows(2) {
+        let previous_height = *pair[0].header().height();
+        let height = *pair[1].header().height();
+
+        if previous_height.succ() != Some(height) {
+            return Err(Error::NonContiguousBlocks {
+                previous_height: previous_height.into(),
+                height: height.into(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates the contiguous `blocks` one after another on top of the `storage` witness
+/// and its recorded `ranges`, checking that each of them commits to the blocks before it.
+/// Fails if the `relayer` witness contains events that none of the blocks asked for.
+/// Returns the chain id of every block, from the consensus parameters it was validated with.
+pub(crate) fn execute_blocks(
+    blocks: &[Block],
+    storage: &Changes,
+    ranges: Vec<RangeRead>,
+    relayer: &Relayer,
+) -> BlockRangeExecutionGameResult<Vec<ChainId>> {
+    check_contiguous(blocks)?;
+
+    // Writes of the previous blocks are layered on top of the witness
+    // and may be overwritten by the blocks that follow.
+    let witness = WitnessStorage::new(witness_ref(storage)).with_ranges(ranges);
+    let mut storage =
+        StorageTransaction::transaction(&witness, ConflictPolicy::Overwrite, Changes::default());
+
+    let mut chain_ids = Vec::with_capacity(blocks.len());
+
+    for block in blocks {
+        let block_height = u32::from(*block.header().height());
+
+        check_prev_root(&storage, block).map_err(|e| match missing_witness(&witness) {
+            Some(error) => Error::FailedExecution {
+                block_height,
+                error,
+            },
+            None => e,
+        })?;
+
+        let changes = validate_block(block, &storage, &witness, relayer).map_err(|error| {
+            Error::FailedExecution {
+                block_height,
+                error,
+            }
+        })?;
+
+        chain_ids.push(import_block(&mut storage, block, changes)?);
+    }
+
+    // The event inbox roots only commit to the events of the DA heights the blocks asked for.
+    if let Some(da_height) = relayer.first_unused_da_height() {
+        return Err(Error::UnusedRelayerEvents {
+            da_height: da_height.0,
+        });
+    }
+
+    Ok(chain_ids)
+}
+
+pub fn prove(input_bytes: &[u8]) -> BlockRangeExecutionGameResult<PublicValuesStruct> {
+    let envelope =
+        Envelope::open(Game::BlockRangeExecution, input_bytes).map_err(Error::InvalidEnvelope)?;
+
+    let input = match envelope.version {
+        INPUT_VERSION => codec::deserialize(envelope.payload),
+        // The legacy inputs share the payload layout with the first version,
+        // which lacks the range reads.
+        LEGACY_VERSION | INPUT_VERSION_WITHOUT_RANGES => {
+            codec::deserialize(envelope.payload).map(|(blocks, storage, relayer)| Input {
+                blocks,
+                storage,
+                ranges: Vec::new(),
+                relayer,
+            })
+        }
+        version => return Err(Error::UnsupportedInputVersion(version)),
+    };
+
+    let Input {
+        blocks,
+        storage,
+        ranges,
+        relayer,
+    } = input.map_err(|e| Error::FailedDecodeInput(e.to_string()))?;
+
+    execute_blocks(&blocks, &storage, ranges, &relayer)?;
+
+    let (Some(first_block), Some(last_block)) = (blocks.first(), blocks.last()) else {
+        return Err(Error::NoBlocks);
+    };
+
+    // Prepare return values
+    let input_hash = fuel_crypto::Hasher::hash(input_bytes);
+    let first_block_id = first_block.header().id();
+    let first_block_height = u32::from(*first_block.header().height());
+    let last_block_id = last_block.header().id();
+    let last_block_height = u32::from(*last_block.header().height());
+
+    Ok(PublicValuesStruct {
+        input_hash: U256::from_be_bytes(input_hash.into()),
+        first_block_id: U256::from_be_bytes(first_block_id.into()),
+        first_block_height: U256::from(first_block_height),
+        last_block_id: U256::from_be_bytes(last_block_id.into()),
+        last_block_height: U256::from(last_block_height),
+    })
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use fuel_core_types::fuel_types::BlockHeight;
+
+    fn block_at_height(height: u32) -> Block {
+        let mut block = Block::default();
+        block
+            .header_mut()
+            .set_block_height(BlockHeight::from(height));
+        block
+    }
+
+    #[test]
+    fn prove_fails__if_no_blocks_provided() {
+        let input = Input {
+            blocks: vec![],
+            storage: Default::default(),
+            ranges: vec![],
+            relayer: Relayer::new(),
+        };
+
+        let result = prove(&encode_input(&input).unwrap());
+
+        assert!(matches!(result, Err(Error::NoBlocks)));
+    }
+
+    #[test]
+    fn prove_fails__if_blocks_are_not_contiguous() {
+        let input = Input {
+            blocks: vec![block_at_height(5), block_at_height(7)],
+            storage: Default::default(),
+            ranges: vec![],
+            relayer: Relayer::new(),
+        };
+
+        let result = prove(&encode_input(&input).unwrap());
+
+        assert!(matches!(
+            result,
+            Err(Error::NonContiguousBlocks {
+                previous_height: 5,
+                height: 7
+            })
+        ));
+    }
+
+    #[test]
+    fn prove_fails__if_input_version_is_unsupported() {
+        let input_bytes = Envelope {
+            game: Game::BlockRangeExecution,
+            version: INPUT_VERSION + 1,
+            payload: &[],
+        }
+        .seal();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedInputVersion(version)) if version == INPUT_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn prove__decodes_inputs_without_range_reads() {
+        let payload =
+            codec::serialize(&(Vec::<Block>::new(), Changes::default(), Relayer::new())).unwrap();
+        let input_bytes = Envelope {
+            game: Game::BlockRangeExecution,
+            version: INPUT_VERSION_WITHOUT_RANGES,
+            payload: &payload,
+        }
+        .seal();
+
+        let result = prove(&input_bytes);
+
+        // The input is decoded, so the game gets to check the blocks.
+        assert!(matches!(result, Err(Error::NoBlocks)));
+    }
+
+    #[test]
+    fn prove_fails__if_storage_witness_is_missing_key() {
+        let input = Input {
+            blocks: vec![block_at_height(5), block_at_height(6)],
+            storage: Default::default(),
+            ranges: vec![],
+            relayer: Relayer::new(),
+        };
+
+        let result = prove(&encode_input(&input).unwrap());
+
+        assert!(matches!(
+            result,
+            Err(Error::FailedExecution {
+                block_height: 5,
+                error: block_execution_game::Error::MissingStorageWitness { .. }
+            })
+        ));
+    }
+}
@@ -0,0 +1,393 @@
+use crate::codec;
+use crate::envelope::{self, Envelope, Game};
+use crate::games::{
+    block_execution_game::{RangeRead, Relayer},
+    block_range_execution_game::{self, execute_blocks},
+    decompression_game::{self, decompress, registry::RegistryWitness, Blob},
+};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use alloy_sol_types::{private::U256, sol};
+use fuel_core_storage::transactional::Changes;
+use fuel_core_types::{
+    blockchain::{
+        block::{Block, PartialFuelBlock},
+        header::PartialBlockHeader,
+    },
+    fuel_crypto,
+    fuel_tx::{
+        field::{InputContract, MintAmount, MintAssetId, MintGasPrice, OutputContract},
+        Transaction, UniqueIdentifier,
+    },
+    fuel_types::ChainId,
+};
+
+sol! {
+    /// The public values encoded as a struct that can be easily deserialized inside Solidity.
+    struct PublicValuesStruct {
+        uint256 input_hash;
+        uint256 blobs_hash;
+        uint256 bundle_id;
+        uint256 blob_count;
+        uint256 blocks_root;
+        uint256 first_block_id;
+        uint256 first_block_height;
+        uint256 last_block_id;
+        uint256 last_block_height;
+        uint256 registry_hash;
+    }
+}
+
+/// This is the input to the decompression execution game.
+/// It proves that the DA blobs decode to the same blocks that are executed:
+/// the blobs are decompressed against the `registry` witness like in the decompression game,
+/// the blocks are validated like in the block range execution game,
+/// and every decompressed block and transaction must match the executed one.
+///
+/// Like the storage witness, the registry witness is trusted,
+/// the game publishes its `registry_hash`.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Input {
+    pub raw_da_blobs: Vec<Blob>,
+    pub registry: RegistryWitness,
+    pub blocks: Vec<Block>,
+    pub storage: Changes,
+    pub ranges: Vec<RangeRead>,
+    pub relayer: Relayer,
+}
+
+/// The version of the `Input` encoding produced by [`encode_input`].
+/// The inputs of the first version lack the registry witness and are rejected.
+pub const INPUT_VERSION: u16 = 3;
+
+/// The version of the `Input` encoding before the range reads were recorded.
+const INPUT_VERSION_WITHOUT_RANGES: u16 = 2;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Invalid input envelope: {0}")]
+    InvalidEnvelope(envelope::Error),
+    #[error("Unsupported input version {0}")]
+    UnsupportedInputVersion(u16),
+    #[error("Failed to decode the input: {0}")]
+    FailedDecodeInput(String),
+    #[error("Failed to decompress the blobs: {0}")]
+    FailedDecompression(decompression_game::Error),
+    #[error("The blobs contain {decompressed} blocks, but {executed} blocks are executed")]
+    BlockCountMismatch {
+        decompressed: usize,
+        executed: usize,
+    },
+    #[error("Header of the block at index {index} doesn't match the executed block")]
+    HeaderMismatch { index: usize },
+    #[error("Block at index {index} has {decompressed} transactions, but {executed} are executed")]
+    TransactionCountMismatch {
+        index: usize,
+        decompressed: usize,
+        executed: usize,
+    },
+    #[error(
+        "Transaction {transaction_index} of the block at index {index} \
+        doesn't match the executed transaction"
+    )]
+    TransactionMismatch {
+        index: usize,
+        transaction_index: usize,
+    },
+    #[error("Failed to execute the blocks: {0}")]
+    FailedExecution(block_range_execution_game::Error),
+}
+
+pub type DecompressionExecutionGameResult<T> = core::result::Result<T, Error>;
+
+/// Encodes the `input` into an envelope of the current [`INPUT_VERSION`].
+pub fn encode_input(input: &Input) -> Result<Vec<u8>, codec::EncodeError> {
+    let payload = codec::serialize(input)?;
+
+    Ok(Envelope {
+        game: Game::DecompressionExecution,
+        version: INPUT_VERSION,
+        payload: &payload,
+    }
+    .seal())
+}
+
+/// Checks that every decompressed block has the header and the number of transactions
+/// of the executed block at the same position.
+fn match_blocks(
+    decompressed_blocks: &[PartialFuelBlock],
+    blocks: &[Block],
+) -> DecompressionExecutionGameResult<()> {
+    if decompressed_blocks.len() != blocks.len() {
+        return Err(Error::BlockCountMismatch {
+            decompressed: decompressed_blocks.len(),
+            executed: blocks.len(),
+        });
+    }
+
+    for (index, (decompressed_block, block)) in decompressed_blocks.iter().zip(blocks).enumerate() {
+        if decompressed_block.header != PartialBlockHeader::from(block.header()) {
+            return Err(Error::HeaderMismatch { index });
+        }
+
+        let decompressed = decompressed_block.transactions.len();
+        let executed = block.transactions().len();
+        if decompressed != executed {
+            return Err(Error::TransactionCountMismatch {
+                index,
+                decompressed,
+                executed,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that the `decompressed` transaction is the `executed` one, by the ids
+/// under the `chain_id` of the block.
+///
+/// The id of the mint transaction covers the fields the executor fills in,
+/// which the compressed block lacks, so it is compared by the compressed fields instead.
+fn transaction_matches(
+    decompressed: &Transaction,
+    executed: &Transaction,
+    chain_id: &ChainId,
+) -> bool {
+    match (decompressed, executed) {
+        (Transaction::Mint(decompressed), Transaction::Mint(executed)) => {
+            decompressed.input_contract().contract_id == executed.input_contract().contract_id
+                && decompressed.output_contract().input_index
+                    == executed.output_contract().input_index
+                && decompressed.mint_amount() == executed.mint_amount()
+                && decompressed.mint_asset_id() == executed.mint_asset_id()
+                && decompressed.gas_price() == executed.gas_price()
+        }
+        (decompressed, executed) => decompressed.id(chain_id) == executed.id(chain_id),
+    }
+}
+
+/// Checks that every decompressed transaction matches the executed transaction
+/// at the same position.
+fn match_transactions(
+    decompressed_blocks: &[PartialFuelBlock],
+    blocks: &[Block],
+    chain_ids: &[ChainId],
+) -> DecompressionExecutionGameResult<()> {
+    let blocks = decompressed_blocks.iter().zip(blocks).zip(chain_ids);
+
+    for (index, ((decompressed_block, block), chain_id)) in blocks.enumerate() {
+        let transactions = decompressed_block
+            .transactions
+            .iter()
+            .zip(block.transactions());
+
+        for (transaction_index, (decompressed, executed)) in transactions.enumerate() {
+            if !transaction_matches(decompressed, executed, chain_id) {
+                return Err(Error::TransactionMismatch {
+                    index,
+                    transaction_index,
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn prove(input_bytes: &[u8]) -> DecompressionExecutionGameResult<PublicValuesStruct> {
+    let envelope = Envelope::open(Game::DecompressionExecution, input_bytes)
+        .map_err(Error::InvalidEnvelope)?;
+
+    let input = match envelope.version {
+        INPUT_VERSION => codec::deserialize(envelope.payload),
+        INPUT_VERSION_WITHOUT_RANGES => codec::deserialize(envelope.payload).map(
+            |(raw_da_blobs, registry, blocks, storage, relayer)| Input {
+                raw_da_blobs,
+                registry,
+                blocks,
+                storage,
+                ranges: Vec::new(),
+                relayer,
+            },
+        ),
+        version => return Err(Error::UnsupportedInputVersion(version)),
+    };
+
+    let Input {
+        raw_da_blobs,
+        registry,
+        blocks,
+        storage,
+        ranges,
+        relayer,
+    } = input.map_err(|e| Error::FailedDecodeInput(e.to_string()))?;
+
+    // The blobs are checked to be valid EIP-4844 blobs, as only those are posted on L1.
+    let (decompression, compressed_blocks) =
+        decompress(raw_da_blobs, true).map_err(Error::FailedDecompression)?;
+
+    let registry_hash = registry.hash().map_err(Error::FailedDecompression)?;
+    let decompressed_blocks = registry
+        .decompress(compressed_blocks)
+        .map_err(Error::FailedDecompression)?;
+
+    match_blocks(&decompressed_blocks, &blocks)?;
+
+    let chain_ids =
+        execute_blocks(&blocks, &storage, ranges, &relayer).map_err(Error::FailedExecution)?;
+
+    match_transactions(&decompressed_blocks, &blocks, &chain_ids)?;
+
+    // The blocks match the non-empty bundle, so there is at least one of them.
+    let (Some(first_block), Some(last_block)) = (blocks.first(), blocks.last()) else {
+        return Err(Error::BlockCountMismatch {
+            decompressed: decompressed_blocks.len(),
+            executed: blocks.len(),
+        });
+    };
+
+    // Prepare return values
+    let input_hash = fuel_crypto::Hasher::hash(input_bytes);
+    let first_block_id = first_block.header().id();
+    let last_block_id = last_block.header().id();
+
+    Ok(PublicValuesStruct {
+        input_hash: U256::from_be_bytes(input_hash.into()),
+        blobs_hash: decompression.blobs_hash,
+        bundle_id: decompression.bundle_id,
+        blob_count: decompression.blob_count,
+        blocks_root: decompression.blocks_root,
+        first_block_id: U256::from_be_bytes(first_block_id.into()),
+        first_block_height: decompression.first_block_height,
+        last_block_id: U256::from_be_bytes(last_block_id.into()),
+        last_block_height: decompression.last_block_height,
+        registry_hash: U256::from_be_bytes(registry_hash.into()),
+    })
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use fuel_block_committer_encoding::{blob, bundle};
+    use fuel_core_compression::VersionedCompressedBlock;
+    use fuel_core_types::fuel_types::BlockHeight;
+
+    fn block_at_height(height: u32) -> Block {
+        let mut block = Block::default();
+        block
+            .header_mut()
+            .set_block_height(BlockHeight::from(height));
+        block
+    }
+
+    fn blobs_of_blocks(blocks: &[Block]) -> Vec<Blob> {
+        let blocks = blocks
+            .iter()
+            .map(|block| {
+                let mut compressed_block = VersionedCompressedBlock::default();
+                let VersionedCompressedBlock::V0(payload) = &mut compressed_block;
+                payload.header = PartialBlockHeader::from(block.header());
+                postcard::to_allocvec(&compressed_block).unwrap()
+            })
+            .collect();
+
+        let blocks = bundle::Bundle::V1(bundle::BundleV1 { blocks });
+
+        let blocks_encoded = bundle::Encoder::default().encode(blocks).unwrap();
+
+        let blobs = blob::Encoder::default().encode(&blocks_encoded, 0).unwrap();
+
+        blobs.into_iter().map(Blob::from).collect()
+    }
+
+    fn prove_blocks(
+        compressed: &[Block],
+        executed: Vec<Block>,
+    ) -> DecompressionExecutionGameResult<PublicValuesStruct> {
+        let input = Input {
+            raw_da_blobs: blobs_of_blocks(compressed),
+            registry: RegistryWitness::default(),
+            blocks: executed,
+            storage: Default::default(),
+            ranges: vec![],
+            relayer: Relayer::new(),
+        };
+
+        prove(&encode_input(&input).unwrap())
+    }
+
+    #[test]
+    fn prove_fails__if_input_is_for_another_game() {
+        let input_bytes = Envelope {
+            game: Game::Decompression,
+            version: INPUT_VERSION,
+            payload: &[],
+        }
+        .seal();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(result, Err(Error::InvalidEnvelope(_))));
+    }
+
+    #[test]
+    fn prove_fails__if_input_lacks_registry_witness() {
+        let input_bytes = Envelope {
+            game: Game::DecompressionExecution,
+            version: 1,
+            payload: &[],
+        }
+        .seal();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(result, Err(Error::UnsupportedInputVersion(1))));
+    }
+
+    #[test]
+    fn prove_fails__if_blobs_are_invalid() {
+        let input = Input {
+            raw_da_blobs: vec![Blob::default()],
+            registry: RegistryWitness::default(),
+            blocks: vec![block_at_height(5)],
+            storage: Default::default(),
+            ranges: vec![],
+            relayer: Relayer::new(),
+        };
+
+        let result = prove(&encode_input(&input).unwrap());
+
+        assert!(matches!(result, Err(Error::FailedDecompression(_))));
+    }
+
+    #[test]
+    fn prove_fails__if_block_is_missing_from_blobs() {
+        let blocks = vec![block_at_height(5), block_at_height(6)];
+
+        let result = prove_blocks(&blocks[..1], blocks.clone());
+
+        assert!(matches!(
+            result,
+            Err(Error::BlockCountMismatch {
+                decompressed: 1,
+                executed: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn prove_fails__if_headers_differ() {
+        let compressed = vec![block_at_height(5), block_at_height(6)];
+        let mut executed = compressed.clone();
+        executed[1].header_mut().set_da_height(1u64.into());
+
+        let result = prove_blocks(&compressed, executed);
+
+        assert!(matches!(result, Err(Error::HeaderMismatch { index: 1 })));
+    }
+}
@@ -1,10 +1,23 @@
+use crate::codec;
+use crate::envelope::{self, Envelope, Game, LEGACY_VERSION};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec::Vec,
+};
 use alloy_sol_types::{private::U256, sol};
 use fuel_block_committer_encoding::{
     blob::{self},
     bundle,
 };
 use fuel_core_compression::VersionedCompressedBlock;
-extern crate alloc;
+use fuel_core_types::{
+    fuel_crypto, fuel_merkle::binary::root_calculator::MerkleRootCalculator, fuel_types::Bytes32,
+};
+use registry::RegistryWitness;
+use versions::{BundleExt, CompressedBlockExt, Versioned};
+
+pub mod registry;
 
 #[derive(Clone)]
 pub struct Blob {
@@ -33,13 +46,35 @@ impl<'de> serde::Deserialize<'de> for Blob {
     }
 }
 
+/// The modulus of the BLS12-381 scalar field, big-endian.
+/// Every 32-byte chunk of a blob posted on L1 is an element of this field.
+const BLS_MODULUS: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
 impl Blob {
-    pub fn new(raw_blob: Vec<u8>) -> Self {
-        let mut inner = [0; 131072];
-        inner[..raw_blob.len()].copy_from_slice(&raw_blob);
-        Self {
-            _inner: Box::new(inner),
-        }
+    /// Creates the blob from the `raw_blob`, padding it with zeroes up to 131072 bytes.
+    /// Fails if the `raw_blob` is longer than that.
+    pub fn new(raw_blob: Vec<u8>) -> DecompressionGameResult<Self> {
+        let mut inner = Box::new([0; 131072]);
+        inner
+            .get_mut(..raw_blob.len())
+            .ok_or(Error::BlobTooLong {
+                len: raw_blob.len(),
+            })?
+            .copy_from_slice(&raw_blob);
+
+        Ok(Self { _inner: inner })
+    }
+
+    /// Returns the index of the first 32-byte chunk that isn't a canonical
+    /// BLS12-381 scalar field element, if any.
+    /// EIP-4844 rejects such blobs, so they can't have been posted on L1.
+    pub fn first_non_canonical_element(&self) -> Option<usize> {
+        self._inner
+            .chunks_exact(32)
+            .position(|element| element >= BLS_MODULUS.as_slice())
     }
 
     fn into_inner(self) -> Box<[u8; 131072]> {
@@ -77,46 +112,258 @@ impl From<Box<[u8; 131072]>> for Blob {
 /// This is the input to the decompression game.
 /// We perform the following validation:
 /// 1. gzip decompress the blob into a set of compressed blocks
-/// 2. TBD
+/// 2. postcard decode every compressed block
+/// 3. if the `registry` is provided, decompress every block against it, one after another
+///
+/// The registry witness is trusted, the game publishes its `registry_hash` instead,
+/// zero if the witness isn't provided.
+/// A decompressed block still lacks the header fields produced by the execution,
+/// so its ID can't be derived without executing it.
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Input {
     // a set of blobs make up a compressed bundle
     // a compressed bundle is made of several bundles
     // each bundle is made of several da compressed block
     pub raw_da_blobs: Vec<Blob>,
+    pub registry: Option<RegistryWitness>,
 }
 
 sol! {
     /// The public values encoded as a struct that can be easily deserialized inside Solidity.
     struct PublicValuesStruct {
+        uint256 blobs_hash;
+        uint256 bundle_id;
+        uint256 blob_count;
+        uint256 blocks_root;
         uint256 first_block_height;
         uint256 last_block_height;
+        uint256 registry_hash;
     }
 }
 
-#[derive(Debug, thiserror::Error)]
+/// The version of the `Input` encoding produced by [`encode_input`].
+pub const INPUT_VERSION: u16 = 2;
+
+/// The version of the `Input` encoding before the registry witness was added.
+const INPUT_VERSION_WITHOUT_REGISTRY: u16 = 1;
+
+/// The decompression failure, pointing at the part of the DA posting that is bad.
+///
+/// Blob indices refer to the order of the blobs in the input, block indices refer to
+/// the order of the compressed blocks in the bundle. The errors of the underlying decoders
+/// are kept as their messages, so the whole error can be serialized and sent to the host.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error, serde::Serialize, serde::Deserialize)]
 pub enum Error {
-    #[error("Invalid input provided")]
-    BadInput,
-    #[error("Failed to decode raw bytes into bundle")]
-    FailedDecodeIntoBundle,
+    #[error("Invalid input provided: {0}")]
+    BadInput(String),
+    #[error("Invalid input envelope: {0}")]
+    InvalidEnvelope(envelope::Error),
+    #[error("Unsupported input version {0}")]
+    UnsupportedInputVersion(u16),
+    #[error("Blob is {len} bytes long, it can't be longer than 131072 bytes")]
+    BlobTooLong { len: usize },
+    #[error("Element {element_index} of the blob {blob_index} isn't a canonical field element")]
+    NonCanonicalFieldElement {
+        blob_index: usize,
+        element_index: usize,
+    },
+    #[error("No blobs provided")]
+    NoBlobs,
+    #[error("Failed to decode the header of the blob {blob_index}: {reason}")]
+    FailedDecodeBlobHeader { blob_index: usize, reason: String },
+    #[error("Failed to decode raw bytes into bundle: {0}")]
+    FailedDecodeIntoBundle(String),
+    #[error("Blob {blob_index} belongs to the bundle {actual}, expected the bundle {expected}")]
+    MixedBundle {
+        blob_index: usize,
+        expected: u32,
+        actual: u32,
+    },
+    #[error("Blobs of the bundle {bundle_id} are missing or duplicated")]
+    IncompleteBundle { bundle_id: u32 },
     #[error("Failed to decode bundle into blocks: {0}")]
     FailedDecodeIntoBlocks(String),
-    #[error("Failed to decode blocks into single block: {0}")]
-    FailedDecodeIntoSingleBlock(String),
+    #[error("Block {block_index} has unsupported compressed block version {version}: {reason}")]
+    UnsupportedCompressedBlockVersion {
+        block_index: usize,
+        version: u32,
+        reason: String,
+    },
+    #[error("Failed to decode the block {block_index}: {reason}")]
+    FailedDecodeIntoSingleBlock { block_index: usize, reason: String },
+    #[error("Failed to decompress the block {block_index}: {reason}")]
+    FailedDecompressBlock { block_index: usize, reason: String },
     #[error("Failed to get first block")]
     FailedToGetFirstBlock,
     #[error("Failed to get last block")]
     FailedToGetLastBlock,
+    #[error("Block {block_index} has height {height}, which doesn't follow the height {previous_height}")]
+    NonContiguousBlockHeights {
+        block_index: usize,
+        previous_height: u32,
+        height: u32,
+    },
 }
 
 pub type DecompressionGameResult<T> = core::result::Result<T, Error>;
 
-pub fn prove(input_bytes: &[u8]) -> DecompressionGameResult<PublicValuesStruct> {
-    let input: Input = bincode::deserialize_from(input_bytes).map_err(|_| Error::BadInput)?;
+/// Computes the hash chain over the raw bytes of the `blobs`, in the order they are provided:
+/// starting from the zero hash, every blob is folded in as `sha256(previous || blob)`.
+///
+/// The games hash the blobs sorted by their index in the bundle, as [`order_blobs`] returns them.
+/// The verifier recomputes the chain from the blob data it retrieved from the beacon chain.
+pub fn blobs_hash(blobs: &[Blob]) -> Bytes32 {
+    blobs.iter().fold(Bytes32::zeroed(), |previous, blob| {
+        fuel_crypto::Hasher::default()
+            .chain(previous)
+            .chain(blob._inner.as_slice())
+            .finalize()
+    })
+}
+
+/// Encodes the `input` into an envelope of the current [`INPUT_VERSION`].
+pub fn encode_input(input: &Input) -> Result<Vec<u8>, codec::EncodeError> {
+    let payload = codec::serialize(input)?;
+
+    Ok(Envelope {
+        game: Game::Decompression,
+        version: INPUT_VERSION,
+        payload: &payload,
+    }
+    .seal())
+}
+
+/// Returns the version and the payload of enveloped or legacy `input_bytes`.
+fn open_input(input_bytes: &[u8]) -> DecompressionGameResult<(u16, &[u8])> {
+    let envelope =
+        Envelope::open(Game::Decompression, input_bytes).map_err(Error::InvalidEnvelope)?;
 
-    let Input { raw_da_blobs } = input;
+    match envelope.version {
+        LEGACY_VERSION | INPUT_VERSION_WITHOUT_REGISTRY | INPUT_VERSION => {
+            Ok((envelope.version, envelope.payload))
+        }
+        version => Err(Error::UnsupportedInputVersion(version)),
+    }
+}
+
+/// Decodes the `Input` from enveloped or legacy `input_bytes`.
+pub fn decode_input(input_bytes: &[u8]) -> DecompressionGameResult<Input> {
+    let (version, payload) = open_input(input_bytes)?;
+
+    let input = if version == INPUT_VERSION {
+        codec::deserialize(payload)
+    } else {
+        // The legacy inputs share the payload layout with the first version,
+        // which holds only the blobs.
+        codec::deserialize(payload).map(|raw_da_blobs| Input {
+            raw_da_blobs,
+            registry: None,
+        })
+    };
+
+    input.map_err(|e| Error::BadInput(e.to_string()))
+}
+
+/// Hashes the postcard encoded compressed block, as it is stored in the bundle:
+/// `sha256(raw_block)`. These hashes are the leaves of the `blocks_root`.
+pub fn compressed_block_hash(raw_block: &[u8]) -> Bytes32 {
+    fuel_crypto::Hasher::hash(raw_block)
+}
+
+/// Computes the `fuel-merkle` binary Merkle root over the [`compressed_block_hash`]
+/// of every block in the bundle, in the order the bundle stores them.
+///
+/// The leaves aren't block IDs: the ID covers the header fields produced by the execution,
+/// which the DA data lacks. A block is proven to be in the bundle with its [`BlockInclusionProof`].
+pub fn blocks_root(raw_blocks: &[Vec<u8>]) -> Bytes32 {
+    let mut root_calculator = MerkleRootCalculator::new();
 
+    for raw_block in raw_blocks {
+        root_calculator.push(compressed_block_hash(raw_block).as_ref());
+    }
+
+    root_calculator.root().into()
+}
+
+/// The bundle reassembled from the blobs.
+pub struct RawBundle {
+    pub id: u32,
+    pub blob_count: usize,
+    /// The postcard encoded compressed blocks.
+    pub blocks: Vec<Vec<u8>>,
+}
+
+/// Checks that the blobs are exactly the blobs of a single bundle.
+/// Returns the bundle id and the blob indices ordered by the position of the blobs in the bundle.
+///
+/// The blobs may come in any order, but every index from zero up to
+/// the blob marked as the last one must be present exactly once.
+fn check_bundle<'a>(
+    blob_decoder: &blob::Decoder,
+    raw_da_blobs: impl IntoIterator<Item = &'a Box<[u8; 131072]>>,
+) -> DecompressionGameResult<(u32, Vec<usize>)> {
+    let mut headers = raw_da_blobs
+        .into_iter()
+        .enumerate()
+        .map(
+            |(blob_index, raw_blob)| match blob_decoder.read_header(raw_blob) {
+                Ok(blob::Header::V1(header)) => Ok((blob_index, header)),
+                Err(e) => Err(Error::FailedDecodeBlobHeader {
+                    blob_index,
+                    reason: e.to_string(),
+                }),
+            },
+        )
+        .collect::<DecompressionGameResult<Vec<_>>>()?;
+
+    let bundle_id = headers.first().ok_or(Error::NoBlobs)?.1.bundle_id;
+
+    if let Some((blob_index, header)) = headers
+        .iter()
+        .find(|(_, header)| header.bundle_id != bundle_id)
+    {
+        return Err(Error::MixedBundle {
+            blob_index: *blob_index,
+            expected: bundle_id,
+            actual: header.bundle_id,
+        });
+    }
+
+    headers.sort_unstable_by_key(|(_, header)| header.idx);
+
+    let last_position = headers.len().saturating_sub(1);
+    let is_complete = headers.iter().enumerate().all(|(position, (_, header))| {
+        usize::try_from(header.idx) == Ok(position) && header.is_last == (position == last_position)
+    });
+
+    if !is_complete {
+        return Err(Error::IncompleteBundle { bundle_id });
+    }
+
+    let order = headers
+        .into_iter()
+        .map(|(blob_index, _)| blob_index)
+        .collect();
+
+    Ok((bundle_id, order))
+}
+
+/// Orders the blobs of a single bundle by their position in the bundle,
+/// checking that none of the blobs is missing.
+pub fn order_blobs(raw_da_blobs: Vec<Blob>) -> DecompressionGameResult<Vec<Blob>> {
+    let blob_decoder = blob::Decoder::default();
+    let (_, order) = check_bundle(&blob_decoder, raw_da_blobs.iter().map(|blob| &blob._inner))?;
+
+    let mut raw_da_blobs = raw_da_blobs.into_iter().map(Some).collect::<Vec<_>>();
+
+    Ok(order
+        .into_iter()
+        .filter_map(|blob_index| raw_da_blobs.get_mut(blob_index).and_then(Option::take))
+        .collect())
+}
+
+/// Decodes the blobs into the postcard encoded compressed blocks of the bundle.
+pub fn decode_raw_bundle(raw_da_blobs: Vec<Blob>) -> DecompressionGameResult<RawBundle> {
     let blob_decoder = blob::Decoder::default();
 
     let raw_da_blobs = raw_da_blobs
@@ -124,9 +371,11 @@ pub fn prove(input_bytes: &[u8]) -> DecompressionGameResult<PublicValuesStruct>
         .map(Blob::into_inner)
         .collect::<Vec<_>>();
 
+    let (id, _) = check_bundle(&blob_decoder, &raw_da_blobs)?;
+
     let compressed_bundle = blob_decoder
         .decode(raw_da_blobs.as_slice())
-        .map_err(|_| Error::FailedDecodeIntoBundle)?;
+        .map_err(|e| Error::FailedDecodeIntoBundle(e.to_string()))?;
 
     let bundle_decoder = bundle::Decoder::default();
 
@@ -134,35 +383,263 @@ pub fn prove(input_bytes: &[u8]) -> DecompressionGameResult<PublicValuesStruct>
         .decode(compressed_bundle.as_slice())
         .map_err(|e| Error::FailedDecodeIntoBlocks(e.to_string()))?;
 
-    let blocks = match bundle {
-        bundle::Bundle::V1(v1_bundle) => {
-            let raw_blocks = v1_bundle.blocks;
-            raw_blocks
-                .iter()
-                .map(|raw_block| postcard::from_bytes::<VersionedCompressedBlock>(raw_block))
-                .collect::<Result<Vec<_>, _>>()
+    let blocks = bundle.into_raw_blocks();
+
+    Ok(RawBundle {
+        id,
+        blob_count: raw_da_blobs.len(),
+        blocks,
+    })
+}
+
+/// Proof that a block of the bundle is included in the `blocks_root`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockInclusionProof {
+    /// Position of the block in the bundle.
+    pub index: u64,
+    /// The [`compressed_block_hash`] of the block.
+    pub block_hash: Bytes32,
+    /// Sibling hashes from the leaf up to the root.
+    pub proof_set: Vec<Bytes32>,
+}
+
+/// Builds the inclusion proofs of every block in the bundle against its `blocks_root`.
+///
+/// Meant to be used on the host, alongside the proof of the game.
+#[cfg(feature = "std")]
+pub fn block_inclusion_proofs(
+    raw_da_blobs: Vec<Blob>,
+) -> DecompressionGameResult<Vec<BlockInclusionProof>> {
+    use fuel_core_types::fuel_merkle::binary::in_memory::MerkleTree;
+
+    let block_hashes = decode_raw_bundle(raw_da_blobs)?
+        .blocks
+        .iter()
+        .map(|raw_block| compressed_block_hash(raw_block))
+        .collect::<Vec<_>>();
+
+    let mut tree = MerkleTree::new();
+    for block_hash in &block_hashes {
+        tree.push(block_hash.as_ref());
+    }
+
+    // The tree has a leaf for every index, so each of them has a proof.
+    let proofs = block_hashes
+        .into_iter()
+        .zip(0u64..)
+        .filter_map(|(block_hash, index)| {
+            let (_, proof_set) = tree.prove(index)?;
+
+            Some(BlockInclusionProof {
+                index,
+                block_hash,
+                proof_set: proof_set.into_iter().map(Bytes32::from).collect(),
+            })
+        })
+        .collect();
+
+    Ok(proofs)
+}
+
+pub fn prove(input_bytes: &[u8]) -> DecompressionGameResult<PublicValuesStruct> {
+    prove_with(input_bytes, false)
+}
+
+/// Same as [`prove`], but also fails if any of the blobs isn't made of canonical
+/// BLS12-381 scalar field elements, as required for the blobs posted on L1.
+pub fn prove_canonical(input_bytes: &[u8]) -> DecompressionGameResult<PublicValuesStruct> {
+    prove_with(input_bytes, true)
+}
+
+fn prove_with(input_bytes: &[u8], canonical: bool) -> DecompressionGameResult<PublicValuesStruct> {
+    let Input {
+        raw_da_blobs,
+        registry,
+    } = decode_input(input_bytes)?;
+
+    let (mut public_values, blocks) = decompress(raw_da_blobs, canonical)?;
+
+    if let Some(registry) = registry {
+        public_values.registry_hash = U256::from_be_bytes(registry.hash()?.into());
+        registry.decompress(blocks)?;
+    }
+
+    Ok(public_values)
+}
+
+/// Decodes the blobs into the contiguous compressed blocks of a single bundle.
+///
+/// Meant to be used on the host, to decompress the blocks while recording the [`RegistryWitness`].
+pub fn compressed_blocks(
+    raw_da_blobs: Vec<Blob>,
+) -> DecompressionGameResult<Vec<VersionedCompressedBlock>> {
+    decompress(raw_da_blobs, false).map(|(_, blocks)| blocks)
+}
+
+/// Decodes the blobs into the contiguous compressed blocks of a single bundle,
+/// returning them along with the public values that commit to them.
+/// The `registry_hash` is left zero, the blocks aren't decompressed yet.
+pub(crate) fn decompress(
+    raw_da_blobs: Vec<Blob>,
+    canonical: bool,
+) -> DecompressionGameResult<(PublicValuesStruct, Vec<VersionedCompressedBlock>)> {
+    if canonical {
+        for (blob_index, blob) in raw_da_blobs.iter().enumerate() {
+            if let Some(element_index) = blob.first_non_canonical_element() {
+                return Err(Error::NonCanonicalFieldElement {
+                    blob_index,
+                    element_index,
+                });
+            }
         }
     }
-    .map_err(|e| Error::FailedDecodeIntoSingleBlock(e.to_string()))?;
 
-    let VersionedCompressedBlock::V0(first_block) =
-        blocks.first().ok_or(Error::FailedToGetFirstBlock)?;
-    let VersionedCompressedBlock::V0(last_block) =
-        blocks.last().ok_or(Error::FailedToGetLastBlock)?;
+    let raw_da_blobs = order_blobs(raw_da_blobs)?;
+    let blobs_hash = blobs_hash(&raw_da_blobs);
 
-    let first_block_height = u32::from(*first_block.header.height());
-    let last_block_height = u32::from(*last_block.header.height());
+    let RawBundle {
+        id: bundle_id,
+        blob_count,
+        blocks: raw_blocks,
+    } = decode_raw_bundle(raw_da_blobs)?;
+
+    let blocks = raw_blocks
+        .iter()
+        .enumerate()
+        .map(|(block_index, raw_block)| {
+            postcard::from_bytes::<VersionedCompressedBlock>(raw_block).map_err(|e| {
+                match VersionedCompressedBlock::unsupported_version(raw_block) {
+                    Some(version) => Error::UnsupportedCompressedBlockVersion {
+                        block_index,
+                        version,
+                        reason: e.to_string(),
+                    },
+                    None => Error::FailedDecodeIntoSingleBlock {
+                        block_index,
+                        reason: e.to_string(),
+                    },
+                }
+            })
+        })
+        .collect::<DecompressionGameResult<Vec<_>>>()?;
 
-    Ok(PublicValuesStruct {
+    let heights = blocks
+        .iter()
+        .map(CompressedBlockExt::height)
+        .collect::<Vec<_>>();
+
+    for (block_index, pair) in heights.windows(2).enumerate() {
+        let (previous_height, height) = (pair[0], pair[1]);
+
+        if previous_height.checked_add(1) != Some(height) {
+            return Err(Error::NonContiguousBlockHeights {
+                block_index: block_index.saturating_add(1),
+                previous_height,
+                height,
+            });
+        }
+    }
+
+    let blocks_root = blocks_root(&raw_blocks);
+
+    let first_block_height = *heights.first().ok_or(Error::FailedToGetFirstBlock)?;
+    let last_block_height = *heights.last().ok_or(Error::FailedToGetLastBlock)?;
+
+    let public_values = PublicValuesStruct {
+        blobs_hash: U256::from_be_bytes(blobs_hash.into()),
+        bundle_id: U256::from(bundle_id),
+        blob_count: U256::from(blob_count),
+        blocks_root: U256::from_be_bytes(blocks_root.into()),
         first_block_height: U256::from(first_block_height),
         last_block_height: U256::from(last_block_height),
-    })
+        registry_hash: U256::ZERO,
+    };
+
+    Ok((public_values, blocks))
+}
+
+/// Version independent access to the DA data.
+///
+/// Only the implementations of these traits match on the versions, so supporting
+/// a new compressed block or bundle version doesn't touch the game logic.
+pub(crate) mod versions {
+    use alloc::vec::Vec;
+    use fuel_block_committer_encoding::bundle;
+    use fuel_core_compression::VersionedCompressedBlock;
+    use fuel_core_types::blockchain::header::PartialBlockHeader;
+
+    /// Data prefixed with its version.
+    pub(crate) trait Versioned {
+        /// The latest version the game can decode.
+        const LATEST_VERSION: u32;
+
+        /// The newest version published upstream. The data claiming a version past it
+        /// is corrupt rather than unsupported, as arbitrary bytes read as some version too.
+        const NEWEST_KNOWN_VERSION: u32;
+
+        /// Reads the version the `encoded` data starts with.
+        fn peek_version(encoded: &[u8]) -> Option<u32>;
+
+        /// Returns the version of the `encoded` data if it is a known version
+        /// the game can't decode yet.
+        fn unsupported_version(encoded: &[u8]) -> Option<u32> {
+            Self::peek_version(encoded).filter(|version| {
+                Self::LATEST_VERSION < *version && *version <= Self::NEWEST_KNOWN_VERSION
+            })
+        }
+    }
+
+    impl Versioned for VersionedCompressedBlock {
+        const LATEST_VERSION: u32 = 0;
+
+        /// Newer fuel-core releases add the `V1` compressed block.
+        const NEWEST_KNOWN_VERSION: u32 = 1;
+
+        /// Postcard encodes the variant index of the enum as a varint.
+        fn peek_version(encoded: &[u8]) -> Option<u32> {
+            postcard::take_from_bytes::<u32>(encoded)
+                .ok()
+                .map(|(version, _)| version)
+        }
+    }
+
+    /// Read access to the compressed block of any version.
+    pub(crate) trait CompressedBlockExt {
+        fn header(&self) -> &PartialBlockHeader;
+
+        fn height(&self) -> u32 {
+            u32::from(*self.header().height())
+        }
+    }
+
+    impl CompressedBlockExt for VersionedCompressedBlock {
+        fn header(&self) -> &PartialBlockHeader {
+            match self {
+                VersionedCompressedBlock::V0(block) => &block.header,
+            }
+        }
+    }
+
+    /// Access to the blocks of the bundle of any version.
+    pub(crate) trait BundleExt {
+        /// Returns the postcard encoded compressed blocks.
+        fn into_raw_blocks(self) -> Vec<Vec<u8>>;
+    }
+
+    impl BundleExt for bundle::Bundle {
+        fn into_raw_blocks(self) -> Vec<Vec<u8>> {
+            match self {
+                bundle::Bundle::V1(bundle) => bundle.blocks,
+            }
+        }
+    }
 }
 
 #[allow(non_snake_case)]
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     fn set_height(block: &mut VersionedCompressedBlock, height: u32) {
         match block {
@@ -181,43 +658,88 @@ mod tests {
 
         let bad_input = BadInput { foo: 10 };
 
-        let input_bytes = bincode::serialize(&bad_input).unwrap();
+        let input_bytes = codec::serialize(&bad_input).unwrap();
 
         let result = prove(&input_bytes);
 
-        assert!(matches!(result, Err(Error::BadInput)));
+        assert!(matches!(result, Err(Error::BadInput(_))));
+    }
+
+    #[test]
+    fn prove_fails__if_unsupported_input_version_provided() {
+        let payload = codec::serialize(&Input {
+            raw_da_blobs: vec![],
+            registry: None,
+        })
+        .unwrap();
+
+        let input_bytes = Envelope {
+            game: Game::Decompression,
+            version: u16::MAX,
+            payload: &payload,
+        }
+        .seal();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedInputVersion(version)) if version == u16::MAX
+        ));
+    }
+
+    #[test]
+    fn prove_fails__if_input_is_for_another_game() {
+        let input_bytes = Envelope {
+            game: Game::BlockExecution,
+            version: INPUT_VERSION,
+            payload: &[],
+        }
+        .seal();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(result, Err(Error::InvalidEnvelope(_))));
     }
 
     #[test]
     fn prove_fails__if_invalid_blob_provided() {
         let input = Input {
             raw_da_blobs: vec![Blob::default()],
+            registry: None,
         };
 
-        let input_bytes = bincode::serialize(&input).unwrap();
+        let input_bytes = codec::serialize(&input).unwrap();
 
         let result = prove(&input_bytes);
 
-        assert!(matches!(result, Err(Error::FailedDecodeIntoBundle)));
+        assert!(matches!(
+            result,
+            Err(Error::FailedDecodeBlobHeader { blob_index: 0, .. })
+        ));
     }
 
     #[test]
     fn prove_fails__if_invalid_block_exists_in_bundle() {
-        use rand::Rng;
+        use fuel_core_types::fuel_crypto::Hasher;
 
-        let block_size = 1024;
-        let block_count = 10;
+        // Each block is made of 32 hashes, 1024 bytes of arbitrary data in total.
+        let hashes_per_block = 32u32;
+        let block_count = 10u32;
         let bundle_id = 10;
 
-        let rng = &mut rand::rng();
-
-        let blocks = std::iter::repeat_with(|| {
-            let mut buf = vec![0; block_size as usize];
-            rng.fill(&mut buf[..]);
-            buf
-        })
-        .take(block_count)
-        .collect::<Vec<_>>();
+        let blocks = (0..block_count)
+            .map(|block| {
+                (0..hashes_per_block)
+                    .flat_map(|chunk| {
+                        *Hasher::default()
+                            .chain(block.to_be_bytes())
+                            .chain(chunk.to_be_bytes())
+                            .finalize()
+                    })
+                    .collect::<Vec<u8>>()
+            })
+            .collect::<Vec<_>>();
 
         let blocks = bundle::Bundle::V1(bundle::BundleV1 { blocks });
 
@@ -229,13 +751,17 @@ mod tests {
 
         let input = Input {
             raw_da_blobs: blobs.into_iter().map(Blob::from).collect(),
+            registry: None,
         };
 
-        let input_bytes = bincode::serialize(&input).unwrap();
+        let input_bytes = codec::serialize(&input).unwrap();
 
         let result = prove(&input_bytes);
 
-        assert!(matches!(result, Err(Error::FailedDecodeIntoSingleBlock(_))));
+        assert!(matches!(
+            result,
+            Err(Error::FailedDecodeIntoSingleBlock { block_index: 0, .. })
+        ));
     }
 
     #[test]
@@ -248,45 +774,489 @@ mod tests {
 
         let input = Input {
             raw_da_blobs: blobs.into_iter().map(Blob::from).collect(),
+            registry: None,
         };
 
-        let input_bytes = bincode::serialize(&input).unwrap();
+        let input_bytes = codec::serialize(&input).unwrap();
 
         let result = prove(&input_bytes);
 
         assert!(matches!(result, Err(Error::FailedToGetFirstBlock)));
     }
 
+    /// Encodes enough arbitrary data to span several blobs of the bundle.
+    fn blobs_of_bundle(bundle_id: u32) -> Vec<Blob> {
+        use fuel_core_types::fuel_crypto::Hasher;
+
+        let data = (0..8192u32)
+            .flat_map(|chunk| *Hasher::hash(chunk.to_be_bytes()))
+            .collect::<Vec<u8>>();
+
+        let blobs = blob::Encoder::default().encode(&data, bundle_id).unwrap();
+        assert!(blobs.len() > 1);
+
+        blobs.into_iter().map(Blob::from).collect()
+    }
+
+    #[test]
+    fn prove_fails__if_blobs_of_different_bundles_provided() {
+        let mut raw_da_blobs = blobs_of_bundle(1);
+        raw_da_blobs[1] = blobs_of_bundle(2).remove(1);
+
+        let input_bytes = encode_input(&Input {
+            raw_da_blobs,
+            registry: None,
+        })
+        .unwrap();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::MixedBundle {
+                blob_index: 1,
+                expected: 1,
+                actual: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn prove_fails__if_blob_of_bundle_is_missing() {
+        let mut raw_da_blobs = blobs_of_bundle(1);
+        raw_da_blobs.pop();
+
+        let input_bytes = encode_input(&Input {
+            raw_da_blobs,
+            registry: None,
+        })
+        .unwrap();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::IncompleteBundle { bundle_id: 1 })
+        ));
+    }
+
+    #[test]
+    fn prove_fails__if_blob_of_bundle_is_duplicated() {
+        let mut raw_da_blobs = blobs_of_bundle(1);
+        raw_da_blobs.push(raw_da_blobs[0].clone());
+
+        let input_bytes = encode_input(&Input {
+            raw_da_blobs,
+            registry: None,
+        })
+        .unwrap();
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::IncompleteBundle { bundle_id: 1 })
+        ));
+    }
+
+    #[test]
+    fn order_blobs__orders_blobs_by_position_in_bundle() {
+        let raw_da_blobs = blobs_of_bundle(1);
+        let mut shuffled = raw_da_blobs.clone();
+        shuffled.reverse();
+
+        let ordered = order_blobs(shuffled).unwrap();
+
+        assert_eq!(
+            codec::serialize(&ordered).unwrap(),
+            codec::serialize(&raw_da_blobs).unwrap()
+        );
+    }
+
+    #[test]
+    fn order_blobs_fails__if_blob_of_bundle_is_missing() {
+        let mut raw_da_blobs = blobs_of_bundle(1);
+        raw_da_blobs.remove(0);
+
+        let result = order_blobs(raw_da_blobs);
+
+        assert!(matches!(
+            result,
+            Err(Error::IncompleteBundle { bundle_id: 1 })
+        ));
+    }
+
+    fn blobs_with_heights(heights: impl IntoIterator<Item = u32>) -> Vec<Blob> {
+        let blocks = heights
+            .into_iter()
+            .map(|height| {
+                let mut block = VersionedCompressedBlock::default();
+                set_height(&mut block, height);
+                postcard::to_allocvec(&block).unwrap()
+            })
+            .collect();
+
+        let blocks = bundle::Bundle::V1(bundle::BundleV1 { blocks });
+
+        let blocks_encoded = bundle::Encoder::default().encode(blocks).unwrap();
+
+        let blobs = blob::Encoder::default().encode(&blocks_encoded, 0).unwrap();
+
+        blobs.into_iter().map(Blob::from).collect()
+    }
+
+    /// Returns the blobs of a bundle with a single block, which registers
+    /// enough incompressible addresses to span several blobs.
+    fn blobs_of_large_block() -> Vec<Blob> {
+        use fuel_core_types::{
+            fuel_compression::RegistryKey, fuel_crypto::Hasher, fuel_tx::Address,
+        };
+
+        let mut block = VersionedCompressedBlock::default();
+        set_height(&mut block, 5);
+        let VersionedCompressedBlock::V0(payload) = &mut block;
+        payload.registrations.address = (0..5000u32)
+            .map(|index| {
+                let key = RegistryKey::try_from(index).unwrap();
+                (key, Address::from(*Hasher::hash(index.to_be_bytes())))
+            })
+            .collect();
+
+        let blocks = bundle::Bundle::V1(bundle::BundleV1 {
+            blocks: vec![postcard::to_allocvec(&block).unwrap()],
+        });
+        let blocks_encoded = bundle::Encoder::default().encode(blocks).unwrap();
+        let blobs = blob::Encoder::default().encode(&blocks_encoded, 0).unwrap();
+        assert!(blobs.len() > 1);
+
+        blobs.into_iter().map(Blob::from).collect()
+    }
+
+    fn input_with_heights(heights: impl IntoIterator<Item = u32>) -> Vec<u8> {
+        let input = Input {
+            raw_da_blobs: blobs_with_heights(heights),
+            registry: None,
+        };
+
+        encode_input(&input).unwrap()
+    }
+
+    #[test]
+    fn prove_fails__if_block_heights_have_gap() {
+        let input_bytes = input_with_heights([5, 6, 8]);
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::NonContiguousBlockHeights {
+                block_index: 2,
+                previous_height: 6,
+                height: 8
+            })
+        ));
+    }
+
+    #[test]
+    fn prove_fails__if_block_heights_are_duplicated() {
+        let input_bytes = input_with_heights([5, 5]);
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::NonContiguousBlockHeights {
+                block_index: 1,
+                previous_height: 5,
+                height: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn prove_fails__if_block_heights_are_out_of_order() {
+        let input_bytes = input_with_heights([5, 7, 6]);
+
+        let result = prove(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::NonContiguousBlockHeights { block_index: 1, .. })
+        ));
+    }
+
     #[test]
     fn prove_succeeds__if_valid_blocks_are_provided() {
         let first_height = 5;
         let last_height = 10;
 
-        let mut block_a = VersionedCompressedBlock::V0(Default::default());
-        set_height(&mut block_a, first_height);
-        let block_a = postcard::to_allocvec(&block_a).unwrap();
+        let raw_da_blobs = blobs_with_heights(first_height..=last_height);
+        let expected_blobs_hash = blobs_hash(&raw_da_blobs);
+
+        let input_bytes = encode_input(&Input {
+            raw_da_blobs,
+            registry: None,
+        })
+        .unwrap();
+
+        let result = prove(&input_bytes).unwrap();
+
+        assert_eq!(
+            result.blobs_hash,
+            U256::from_be_bytes(expected_blobs_hash.into())
+        );
+        assert_eq!(result.bundle_id, U256::from(0));
+        assert_eq!(result.blob_count, U256::from(1));
+        assert_eq!(result.first_block_height, U256::from(first_height));
+        assert_eq!(result.last_block_height, U256::from(last_height));
+    }
+
+    #[test]
+    fn prove_succeeds__with_blobs_hash_in_bundle_order_if_blobs_are_shuffled() {
+        let raw_da_blobs = blobs_of_large_block();
+        let expected_blobs_hash = blobs_hash(&raw_da_blobs);
+
+        let mut shuffled = raw_da_blobs;
+        shuffled.reverse();
+        let input_bytes = encode_input(&Input {
+            raw_da_blobs: shuffled,
+            registry: None,
+        })
+        .unwrap();
+
+        let result = prove(&input_bytes).unwrap();
 
-        let mut block_b = VersionedCompressedBlock::default();
-        set_height(&mut block_b, last_height);
-        let block_b = postcard::to_allocvec(&block_b).unwrap();
+        assert_eq!(
+            result.blobs_hash,
+            U256::from_be_bytes(expected_blobs_hash.into())
+        );
+        assert_eq!(result.first_block_height, U256::from(5));
+    }
+
+    #[test]
+    fn prove_succeeds__if_blocks_decompress_against_registry() {
+        let registry = RegistryWitness::default();
+        let expected_registry_hash = registry.hash().unwrap();
 
+        let input = Input {
+            raw_da_blobs: blobs_with_heights(5..=10),
+            registry: Some(registry),
+        };
+
+        let result = prove(&encode_input(&input).unwrap()).unwrap();
+
+        assert_eq!(
+            result.registry_hash,
+            U256::from_be_bytes(expected_registry_hash.into())
+        );
+        assert_ne!(result.registry_hash, U256::ZERO);
+    }
+
+    #[test]
+    fn prove_succeeds__with_zero_registry_hash_if_registry_is_not_provided() {
+        let input_bytes = input_with_heights(5..=10);
+
+        let result = prove(&input_bytes).unwrap();
+
+        assert_eq!(result.registry_hash, U256::ZERO);
+    }
+
+    #[test]
+    fn decode_input__reads_inputs_without_registry() {
+        let payload = codec::serialize(&blobs_with_heights(5..=10)).unwrap();
+        let input_bytes = Envelope {
+            game: Game::Decompression,
+            version: INPUT_VERSION_WITHOUT_REGISTRY,
+            payload: &payload,
+        }
+        .seal();
+
+        let decoded = decode_input(&input_bytes).unwrap();
+
+        assert_eq!(decoded.raw_da_blobs.len(), 1);
+        assert!(decoded.registry.is_none());
+        assert!(prove(&input_bytes).is_ok());
+    }
+
+    #[test]
+    fn blobs_hash__depends_on_the_order_of_blobs() {
+        let mut first = [0; 131072];
+        first[0] = 1;
+        let mut second = [0; 131072];
+        second[0] = 2;
+
+        let blobs = vec![Blob::from(Box::new(first)), Blob::from(Box::new(second))];
+        let reversed = vec![Blob::from(Box::new(second)), Blob::from(Box::new(first))];
+
+        assert_ne!(blobs_hash(&blobs), blobs_hash(&reversed));
+    }
+
+    #[test]
+    fn blobs_hash__is_zero_if_no_blobs_provided() {
+        assert_eq!(blobs_hash(&[]), Bytes32::zeroed());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn block_inclusion_proofs__verify_against_blocks_root() {
+        use fuel_core_types::fuel_merkle::binary::verify;
+
+        let raw_da_blobs = blobs_with_heights(5..=10);
+        let proofs = block_inclusion_proofs(raw_da_blobs.clone()).unwrap();
+
+        let input_bytes = encode_input(&Input {
+            raw_da_blobs,
+            registry: None,
+        })
+        .unwrap();
+        let result = prove(&input_bytes).unwrap();
+        let blocks_root = Bytes32::from(result.blocks_root.to_be_bytes::<32>());
+
+        assert_eq!(proofs.len(), 6);
+        for proof in proofs {
+            let proof_set = proof
+                .proof_set
+                .iter()
+                .map(|hash| **hash)
+                .collect::<Vec<_>>();
+
+            assert!(verify(
+                &blocks_root,
+                &proof.block_hash,
+                &proof_set,
+                proof.index,
+                6
+            ));
+        }
+    }
+
+    #[test]
+    fn blob_new__pads_short_input_with_zeroes() {
+        let blob = Blob::new(vec![1, 2, 3]).unwrap();
+
+        let inner = blob.into_inner();
+        assert_eq!(&inner[..3], &[1, 2, 3]);
+        assert!(inner[3..].iter().all(|byte| *byte == 0));
+    }
+
+    #[test]
+    fn blob_new_fails__if_input_is_too_long() {
+        let result = Blob::new(vec![0; 131073]);
+
+        assert!(matches!(result, Err(Error::BlobTooLong { len: 131073 })));
+    }
+
+    #[test]
+    fn first_non_canonical_element__accepts_largest_field_element() {
+        let mut largest = BLS_MODULUS;
+        largest[31] = 0;
+
+        let blob = Blob::new([[0; 32], largest].concat()).unwrap();
+
+        assert_eq!(blob.first_non_canonical_element(), None);
+    }
+
+    #[test]
+    fn first_non_canonical_element__rejects_modulus() {
+        let blob = Blob::new([[0; 32], [0; 32], BLS_MODULUS].concat()).unwrap();
+
+        assert_eq!(blob.first_non_canonical_element(), Some(2));
+    }
+
+    #[test]
+    fn prove_canonical_fails__if_blob_has_non_canonical_element() {
+        let mut raw_da_blobs = blobs_with_heights(5..=10);
+        raw_da_blobs.push(Blob::new([0xff; 32].to_vec()).unwrap());
+
+        let input_bytes = encode_input(&Input {
+            raw_da_blobs,
+            registry: None,
+        })
+        .unwrap();
+
+        let result = prove_canonical(&input_bytes);
+
+        assert!(matches!(
+            result,
+            Err(Error::NonCanonicalFieldElement {
+                blob_index: 1,
+                element_index: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn prove_canonical_succeeds__if_valid_blocks_are_provided() {
+        let input_bytes = input_with_heights(5..=10);
+
+        let result = prove_canonical(&input_bytes);
+
+        assert!(result.is_ok());
+    }
+
+    /// Encodes the input with a single block of the `raw_block` bytes.
+    fn input_with_raw_block(raw_block: Vec<u8>) -> Vec<u8> {
         let blocks = bundle::Bundle::V1(bundle::BundleV1 {
-            blocks: vec![block_a, block_b],
+            blocks: vec![raw_block],
         });
 
-        let blocks_encoded = bundle::Encoder::default().encode(blocks.clone()).unwrap();
+        let blocks_encoded = bundle::Encoder::default().encode(blocks).unwrap();
 
         let blobs = blob::Encoder::default().encode(&blocks_encoded, 0).unwrap();
 
         let input = Input {
             raw_da_blobs: blobs.into_iter().map(Blob::from).collect(),
+            registry: None,
         };
 
-        let input_bytes = bincode::serialize(&input).unwrap();
+        encode_input(&input).unwrap()
+    }
 
-        let result = prove(&input_bytes).unwrap();
+    #[test]
+    fn prove_fails__if_compressed_block_version_is_unsupported() {
+        let mut block = VersionedCompressedBlock::default();
+        set_height(&mut block, 5);
+        let mut raw_block = postcard::to_allocvec(&block).unwrap();
+        // Replace the variant index of the `V0` with the `V1`, that the game can't decode yet.
+        raw_block[0] = 1;
 
-        assert_eq!(result.first_block_height, U256::from(first_height));
-        assert_eq!(result.last_block_height, U256::from(last_height));
+        let result = prove(&input_with_raw_block(raw_block));
+
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedCompressedBlockVersion {
+                block_index: 0,
+                version: 1,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn prove_fails__if_compressed_block_version_is_unknown() {
+        let mut block = VersionedCompressedBlock::default();
+        set_height(&mut block, 5);
+        let mut raw_block = postcard::to_allocvec(&block).unwrap();
+        // No compressed block version past the `V1` is published, so the block is corrupt.
+        raw_block[0] = 2;
+
+        let result = prove(&input_with_raw_block(raw_block));
+
+        assert!(matches!(
+            result,
+            Err(Error::FailedDecodeIntoSingleBlock { block_index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn error__survives_serialization_round_trip() {
+        let error = Error::FailedDecodeIntoSingleBlock {
+            block_index: 3,
+            reason: "unexpected end of input".to_string(),
+        };
+
+        let bytes = codec::serialize(&error).unwrap();
+
+        assert_eq!(codec::deserialize::<Error>(&bytes).unwrap(), error);
     }
 }
@@ -0,0 +1,403 @@
+//! The temporal registry and the chain history the compressed blocks refer to,
+//! recorded by the host into the [`RegistryWitness`] while it decompresses the blocks itself.
+
+use super::{DecompressionGameResult, Error};
+use crate::codec;
+use alloc::{collections::BTreeMap, format, string::ToString, sync::Arc, task::Wake, vec::Vec};
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use fuel_core_compression::{
+    decompress::{decompress, DecompressDb},
+    ports::{CoinInfo, HistoryLookup, MessageInfo, TemporalRegistry},
+    Config, VersionedCompressedBlock,
+};
+use fuel_core_types::{
+    blockchain::block::PartialFuelBlock,
+    fuel_compression::RegistryKey,
+    fuel_crypto,
+    fuel_tx::{
+        input::PredicateCode, Address, AssetId, CompressedUtxoId, ContractId, ScriptCode, UtxoId,
+    },
+    fuel_types::{Bytes32, Nonce},
+};
+use tai64::Tai64;
+
+/// The registered value along with the time it was registered at.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RegistryEntry<T> {
+    pub value: T,
+    /// The TAI64 timestamp of the block that registered the value.
+    pub timestamp: u64,
+}
+
+/// The coin spent by a compressed coin input.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Coin {
+    pub owner: Address,
+    pub amount: u64,
+    pub asset_id: AssetId,
+}
+
+/// The message spent by a compressed message input.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Message {
+    pub sender: Address,
+    pub recipient: Address,
+    pub amount: u64,
+    pub data: Vec<u8>,
+}
+
+/// The part of the temporal registry and the chain history that the blocks of a bundle read,
+/// as it was before the first of them.
+///
+/// The registrations of every block are written on top of it while decompressing,
+/// so the later blocks of the bundle see the values the earlier ones registered.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RegistryWitness {
+    /// How long a registered value can be referred to after it was registered.
+    pub temporal_registry_retention: Duration,
+    pub addresses: BTreeMap<RegistryKey, RegistryEntry<Address>>,
+    pub asset_ids: BTreeMap<RegistryKey, RegistryEntry<AssetId>>,
+    pub contract_ids: BTreeMap<RegistryKey, RegistryEntry<ContractId>>,
+    pub script_codes: BTreeMap<RegistryKey, RegistryEntry<ScriptCode>>,
+    pub predicate_codes: BTreeMap<RegistryKey, RegistryEntry<PredicateCode>>,
+    /// The outputs the compressed inputs point at.
+    pub utxo_ids: Vec<(CompressedUtxoId, UtxoId)>,
+    pub coins: BTreeMap<UtxoId, Coin>,
+    pub messages: BTreeMap<Nonce, Message>,
+}
+
+impl RegistryWitness {
+    /// Hashes the encoded witness. The hash is a public value of the games,
+    /// so the verifier can check the witness against the registry it trusts.
+    pub fn hash(&self) -> DecompressionGameResult<Bytes32> {
+        let encoded = codec::serialize(self).map_err(|e| Error::BadInput(e.to_string()))?;
+
+        Ok(fuel_crypto::Hasher::hash(encoded))
+    }
+
+    /// Decompresses the `blocks` of the bundle against the witness.
+    pub fn decompress(
+        mut self,
+        blocks: Vec<VersionedCompressedBlock>,
+    ) -> DecompressionGameResult<Vec<PartialFuelBlock>> {
+        let retention = self.temporal_registry_retention;
+
+        decompress_blocks(retention, &mut self, blocks)
+    }
+
+    fn entry<T: Keyspace>(&self, key: &RegistryKey) -> anyhow::Result<&RegistryEntry<T>> {
+        T::keyspace(self).get(key).ok_or_else(|| {
+            anyhow::anyhow!(
+                "The key {key:?} is missing in the {} registry of the witness",
+                T::NAME
+            )
+        })
+    }
+}
+
+/// The values that have their own keyspace in the temporal registry.
+pub trait Keyspace: Clone + PartialEq + Sized {
+    const NAME: &'static str;
+
+    fn keyspace(witness: &RegistryWitness) -> &BTreeMap<RegistryKey, RegistryEntry<Self>>;
+
+    fn keyspace_mut(
+        witness: &mut RegistryWitness,
+    ) -> &mut BTreeMap<RegistryKey, RegistryEntry<Self>>;
+}
+
+macro_rules! keyspace {
+    ($type:ty, $field:ident, $name:literal) => {
+        impl Keyspace for $type {
+            const NAME: &'static str = $name;
+
+            fn keyspace(witness: &RegistryWitness) -> &BTreeMap<RegistryKey, RegistryEntry<Self>> {
+                &witness.$field
+            }
+
+            fn keyspace_mut(
+                witness: &mut RegistryWitness,
+            ) -> &mut BTreeMap<RegistryKey, RegistryEntry<Self>> {
+                &mut witness.$field
+            }
+        }
+    };
+}
+
+keyspace!(Address, addresses, "address");
+keyspace!(AssetId, asset_ids, "asset id");
+keyspace!(ContractId, contract_ids, "contract id");
+keyspace!(ScriptCode, script_codes, "script code");
+keyspace!(PredicateCode, predicate_codes, "predicate code");
+
+impl<T: Keyspace> TemporalRegistry<T> for RegistryWitness {
+    fn read_registry(&self, key: &RegistryKey) -> anyhow::Result<T> {
+        Ok(self.entry::<T>(key)?.value.clone())
+    }
+
+    fn read_timestamp(&self, key: &RegistryKey) -> anyhow::Result<Tai64> {
+        Ok(Tai64(self.entry::<T>(key)?.timestamp))
+    }
+
+    fn write_registry(
+        &mut self,
+        key: &RegistryKey,
+        value: &T,
+        timestamp: Tai64,
+    ) -> anyhow::Result<()> {
+        let entry = RegistryEntry {
+            value: value.clone(),
+            timestamp: timestamp.0,
+        };
+        T::keyspace_mut(self).insert(*key, entry);
+
+        Ok(())
+    }
+
+    fn registry_index_lookup(&self, value: &T) -> anyhow::Result<Option<RegistryKey>> {
+        Ok(T::keyspace(self)
+            .iter()
+            .find(|(_, entry)| entry.value == *value)
+            .map(|(key, _)| *key))
+    }
+}
+
+impl HistoryLookup for RegistryWitness {
+    fn utxo_id(&self, c: CompressedUtxoId) -> anyhow::Result<UtxoId> {
+        self.utxo_ids
+            .iter()
+            .find(|(compressed, _)| *compressed == c)
+            .map(|(_, utxo_id)| *utxo_id)
+            .ok_or_else(|| anyhow::anyhow!("The UTXO {c:?} is missing in the witness"))
+    }
+
+    fn coin(&self, utxo_id: UtxoId) -> anyhow::Result<CoinInfo> {
+        let coin = self
+            .coins
+            .get(&utxo_id)
+            .ok_or_else(|| anyhow::anyhow!("The coin {utxo_id:?} is missing in the witness"))?;
+
+        Ok(CoinInfo {
+            owner: coin.owner,
+            amount: coin.amount,
+            asset_id: coin.asset_id,
+        })
+    }
+
+    fn message(&self, nonce: Nonce) -> anyhow::Result<MessageInfo> {
+        let message = self
+            .messages
+            .get(&nonce)
+            .ok_or_else(|| anyhow::anyhow!("The message {nonce} is missing in the witness"))?;
+
+        Ok(MessageInfo {
+            sender: message.sender,
+            recipient: message.recipient,
+            amount: message.amount,
+            data: message.data.clone(),
+        })
+    }
+}
+
+/// Lends the database to a single decompression, which takes it by value.
+struct ByRef<'a, D>(&'a mut D);
+
+impl<T, D: TemporalRegistry<T>> TemporalRegistry<T> for ByRef<'_, D> {
+    fn read_registry(&self, key: &RegistryKey) -> anyhow::Result<T> {
+        self.0.read_registry(key)
+    }
+
+    fn read_timestamp(&self, key: &RegistryKey) -> anyhow::Result<Tai64> {
+        self.0.read_timestamp(key)
+    }
+
+    fn write_registry(
+        &mut self,
+        key: &RegistryKey,
+        value: &T,
+        timestamp: Tai64,
+    ) -> anyhow::Result<()> {
+        self.0.write_registry(key, value, timestamp)
+    }
+
+    fn registry_index_lookup(&self, value: &T) -> anyhow::Result<Option<RegistryKey>> {
+        self.0.registry_index_lookup(value)
+    }
+}
+
+impl<D: HistoryLookup> HistoryLookup for ByRef<'_, D> {
+    fn utxo_id(&self, c: CompressedUtxoId) -> anyhow::Result<UtxoId> {
+        self.0.utxo_id(c)
+    }
+
+    fn coin(&self, utxo_id: UtxoId) -> anyhow::Result<CoinInfo> {
+        self.0.coin(utxo_id)
+    }
+
+    fn message(&self, nonce: Nonce) -> anyhow::Result<MessageInfo> {
+        self.0.message(nonce)
+    }
+}
+
+/// Decompresses the `blocks` one after another against the `db`,
+/// so the values registered by a block are visible to the blocks after it.
+///
+/// The guest decompresses against the [`RegistryWitness`], the host against its own database
+/// while recording the witness.
+pub fn decompress_blocks<D: DecompressDb>(
+    temporal_registry_retention: Duration,
+    db: &mut D,
+    blocks: Vec<VersionedCompressedBlock>,
+) -> DecompressionGameResult<Vec<PartialFuelBlock>> {
+    blocks
+        .into_iter()
+        .enumerate()
+        .map(|(block_index, block)| {
+            let config = Config {
+                temporal_registry_retention,
+            };
+
+            let result =
+                poll_once(decompress(config, ByRef(&mut *db), block)).unwrap_or_else(|| {
+                    Err(anyhow::anyhow!(
+                        "The decompression waits for an asynchronous lookup"
+                    ))
+                });
+
+            result.map_err(|e| Error::FailedDecompressBlock {
+                block_index,
+                reason: format!("{e:#}"),
+            })
+        })
+        .collect()
+}
+
+/// Polls the `future` once. The decompression doesn't wait for anything,
+/// every lookup of the database is answered right away, so it completes on the first poll.
+fn poll_once<F: Future>(future: F) -> Option<F::Output> {
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    let waker = Waker::from(Arc::new(NoopWaker));
+    let mut context = Context::from_waker(&waker);
+
+    match pin!(future).poll(&mut context) {
+        Poll::Ready(output) => Some(output),
+        Poll::Pending => None,
+    }
+}
+
+#[allow(non_snake_case)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fuel_core_types::blockchain::header::PartialBlockHeader;
+
+    fn key(index: u32) -> RegistryKey {
+        RegistryKey::try_from(index).unwrap()
+    }
+
+    #[test]
+    fn read_registry_fails__if_key_is_missing() {
+        let witness = RegistryWitness::default();
+
+        let result = TemporalRegistry::<Address>::read_registry(&witness, &key(1));
+
+        let error = result.unwrap_err().to_string();
+        assert!(error.contains("address"), "{error}");
+    }
+
+    #[test]
+    fn write_registry__is_visible_to_later_reads() {
+        let mut witness = RegistryWitness::default();
+        witness.addresses.insert(
+            key(1),
+            RegistryEntry {
+                value: Address::from([1; 32]),
+                timestamp: 1,
+            },
+        );
+
+        witness
+            .write_registry(&key(1), &Address::from([2; 32]), Tai64(2))
+            .unwrap();
+
+        assert_eq!(
+            TemporalRegistry::<Address>::read_registry(&witness, &key(1)).unwrap(),
+            Address::from([2; 32])
+        );
+        assert_eq!(
+            TemporalRegistry::<Address>::read_timestamp(&witness, &key(1)).unwrap(),
+            Tai64(2)
+        );
+        assert_eq!(
+            witness
+                .registry_index_lookup(&Address::from([2; 32]))
+                .unwrap(),
+            Some(key(1))
+        );
+    }
+
+    #[test]
+    fn coin_fails__if_coin_is_missing() {
+        let witness = RegistryWitness::default();
+
+        let result = witness.coin(UtxoId::default());
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn decompress__keeps_headers_of_blocks() {
+        let blocks = (5..=7u32)
+            .map(|height| {
+                let mut block = VersionedCompressedBlock::default();
+                let VersionedCompressedBlock::V0(payload) = &mut block;
+                payload.header.consensus.height = height.into();
+                block
+            })
+            .collect::<Vec<_>>();
+        let headers = blocks
+            .iter()
+            .map(|VersionedCompressedBlock::V0(payload)| payload.header.clone())
+            .collect::<Vec<PartialBlockHeader>>();
+
+        let decompressed = RegistryWitness::default().decompress(blocks).unwrap();
+
+        assert_eq!(
+            decompressed
+                .into_iter()
+                .map(|block| {
+                    assert!(block.transactions.is_empty());
+                    block.header
+                })
+                .collect::<Vec<_>>(),
+            headers
+        );
+    }
+
+    #[test]
+    fn hash__depends_on_witness_entries() {
+        let mut witness = RegistryWitness::default();
+        let empty = witness.hash().unwrap();
+
+        witness.coins.insert(
+            UtxoId::default(),
+            Coin {
+                owner: Address::zeroed(),
+                amount: 1,
+                asset_id: AssetId::BASE,
+            },
+        );
+
+        assert_ne!(witness.hash().unwrap(), empty);
+    }
+}
@@ -1,8 +1,15 @@
+#![no_std]
 #![deny(clippy::arithmetic_side_effects)]
 #![deny(clippy::cast_possible_truncation)]
 #![deny(unused_crate_dependencies)]
 #![deny(warnings)]
 
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate std;
+
 use tai64 as _;
 
+pub mod codec;
+pub mod envelope;
 pub mod games;
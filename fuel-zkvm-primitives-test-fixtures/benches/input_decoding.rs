@@ -0,0 +1,80 @@
+//! Compares the cost of decoding the block execution game input
+//! into owned structures against the zero-copy `InputRef`, on the mainnet block fixtures,
+//! along with the whole proving, where the executor copies the values it reads.
+//!
+//! Allocations and allocated bytes are used as a proxy for the guest cycles,
+//! since every allocation and copy of the witness is paid for inside the zkVM.
+
+use fuel_zkvm_primitives_prover::games::block_execution_game;
+use fuel_zkvm_primitives_test_fixtures::block_execution_fixtures::mainnet_blocks::{
+    get_mainnet_block_input, MainnetBlocks,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations and allocated bytes made by `f`.
+fn measure<T>(f: impl FnOnce() -> T) -> (usize, usize) {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    ALLOCATED_BYTES.store(0, Ordering::Relaxed);
+
+    let result = f();
+    let counters = (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    );
+    drop(result);
+
+    counters
+}
+
+fn main() {
+    println!("| fixture | input bytes | path | allocations | allocated bytes |");
+    println!("|---|---|---|---|---|");
+
+    for block in enum_iterator::all::<MainnetBlocks>() {
+        let input = get_mainnet_block_input(block);
+
+        let owned =
+            measure(|| block_execution_game::decode_input(&input).expect("Invalid fixture"));
+        let borrowed =
+            measure(|| block_execution_game::decode_input_ref(&input).expect("Invalid fixture"));
+        let proved = measure(|| block_execution_game::prove(&input).expect("Invalid fixture"));
+
+        for (path, (allocations, allocated_bytes)) in
+            [("owned", owned), ("zero-copy", borrowed), ("prove", proved)]
+        {
+            println!(
+                "| {block:?} | {} | {path} | {allocations} | {allocated_bytes} |",
+                input.len()
+            );
+        }
+    }
+}
@@ -11,6 +11,7 @@ use fuel_core_storage::StorageAsMut;
 use fuel_core_types::entities::contract::ContractUtxoInfo;
 use fuel_core_types::fuel_crypto;
 use fuel_core_types::fuel_tx::{AssetId, Bytes32};
+use fuel_zkvm_primitives_prover::games::block_execution_game;
 use fuel_zkvm_primitives_utils::vm::base::AsRepr;
 use fuel_zkvm_primitives_utils::vm::blob::BlobInstruction;
 use fuel_zkvm_primitives_utils::vm::contract::{ContractInstruction, ContractMetadata};
@@ -207,7 +208,7 @@ pub async fn generate_fixture() -> anyhow::Result<()> {
                     start_node_with_transaction_and_produce_prover_input(instruction.clone())
                         .await?;
 
-                let serialized_prover_input = bincode::serialize(&service.input)?;
+                let serialized_prover_input = block_execution_game::encode_input(&service.input)?;
                 let file_path = format!("src/fixtures/opcodes/{instruction:?}.bin");
                 std::fs::write(file_path, serialized_prover_input)?;
 
@@ -7,13 +7,10 @@ use fuel_core::state::data_source::DataSource;
 use fuel_core::state::historical_rocksdb::description::Historical;
 use fuel_core::state::historical_rocksdb::{HistoricalRocksDB, StateRewindPolicy};
 use fuel_core::state::rocks_db::{ColumnsPolicy, RocksDb};
-use fuel_core_executor::executor::{ExecutionInstance, ExecutionOptions};
-use fuel_core_storage::transactional::{AtomicView, HistoricalView};
 use fuel_core_types::fuel_crypto::SecretKey;
 use fuel_core_types::fuel_tx::{Bytes32, ConsensusParameters};
 use fuel_core_types::fuel_types::BlockHeight;
-use fuel_zkvm_primitives_input_provider::relayer_recorder::RelayerRecorder;
-use fuel_zkvm_primitives_input_provider::storage_access_recorder::StorageAccessRecorder;
+use fuel_zkvm_primitives_input_provider::input_provider::InputProvider;
 use fuel_zkvm_primitives_prover::games::block_execution_game;
 use fuels::prelude::{Provider, WalletUnlocked};
 use std::net::SocketAddr;
@@ -116,37 +113,14 @@ pub async fn generate_input_at_block_height(
     fuel_node: FuelService,
     height: BlockHeight,
 ) -> anyhow::Result<Service> {
-    let on_chain_database = fuel_node.shared.database.on_chain();
-    let block_height_before_tx = height.pred().expect("Impossible");
-    let on_chain_storage_at_height = on_chain_database.view_at(&block_height_before_tx)?;
-
     // We don't need to specify the height for the relayer.
     // Relayer stores events for all height from DA.
-    let latest_relayer = fuel_node.shared.database.relayer().latest_view()?;
-
-    let storage = StorageAccessRecorder::new(on_chain_storage_at_height);
-    let relayer = RelayerRecorder::new(latest_relayer);
-
-    let validator = ExecutionInstance::new(
-        relayer.clone(),
-        storage.clone(),
-        ExecutionOptions {
-            extra_tx_checks: true,
-            backtrace: false,
-        },
+    let provider = InputProvider::new(
+        fuel_node.shared.database.on_chain().clone(),
+        fuel_node.shared.database.relayer().clone(),
     );
 
-    let block = on_chain_database
-        .latest_view()?
-        .get_full_block(&height)?
-        .expect("Block with transaction is not available");
-    let _ = validator.validate_without_commit(&block)?;
-
-    let input = block_execution_game::Input {
-        block,
-        storage: storage.into_changes(),
-        relayer: relayer.into_prover_relayer(),
-    };
+    let input = provider.input_at(height)?;
 
     Ok(Service { fuel_node, input })
 }
@@ -51,10 +51,13 @@ impl clap::ValueEnum for Fixture {
     }
 }
 
+#[allow(non_snake_case)]
 #[cfg(test)]
 mod tests {
     use super::*;
-    use fuel_zkvm_primitives_prover::games::block_execution_game::{prove, Input};
+    use fuel_zkvm_primitives_prover::games::block_execution_game::{
+        decode_input, encode_input, prove, Error,
+    };
     use rayon::prelude::*;
 
     #[test]
@@ -69,10 +72,9 @@ mod tests {
                     return Err(format!("Fixture '{:?}' has empty prover input", fixture));
                 }
 
-                let deserialized_input: Input =
-                    bincode::deserialize(&prover_input).map_err(|e| {
-                        format!("Failed to deserialize fixture '{:?}': {:?}", fixture, e)
-                    })?;
+                let deserialized_input = decode_input(&prover_input).map_err(|e| {
+                    format!("Failed to deserialize fixture '{:?}': {:?}", fixture, e)
+                })?;
 
                 let proof = prove(&prover_input).map_err(|e| {
                     format!(
@@ -93,4 +95,18 @@ mod tests {
             }
         });
     }
+
+    #[test]
+    fn prove_fails__if_relayer_witness_has_unused_events() {
+        let prover_input = Fixture::CounterContract.get_input_for_fixture();
+        let input = decode_input(&prover_input).unwrap();
+        input.relayer.add_event(u64::MAX.into(), vec![]);
+
+        let result = prove(&encode_input(&input).unwrap());
+
+        assert!(matches!(
+            result,
+            Err(Error::UnusedRelayerEvents { da_height }) if da_height == u64::MAX
+        ));
+    }
 }